@@ -0,0 +1,193 @@
+//! Local record of committed transactions, so `history` can list them and
+//! `undo <id>` can reverse one without re-deriving anything from apt.
+//! Backed by SQLite rather than a flat file since `undo` needs to look up
+//! one transaction's packages by id without re-parsing the whole log.
+
+use crate::{AppError, AppResult, Package, ParsedOutput};
+use rusqlite::{params, Connection};
+
+const DB_PATH: &str = "/var/lib/apt-fronted/history.db";
+
+pub struct Transaction {
+	pub id: i64,
+	pub timestamp: i64,
+	pub action: String,
+	pub summary: [i32; 3],
+}
+
+pub struct TransactionPackage {
+	pub name: String,
+	pub old_version: Option<String>,
+	pub new_version: Option<String>,
+	pub operation: String,
+}
+
+fn open() -> AppResult<Connection> {
+	if let Some(parent) = std::path::Path::new(DB_PATH).parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	let conn = Connection::open(DB_PATH).map_err(|e| AppError::Other(e.to_string()))?;
+	conn.execute_batch(
+		"CREATE TABLE IF NOT EXISTS transactions (
+			id INTEGER PRIMARY KEY AUTOINCREMENT,
+			timestamp INTEGER NOT NULL,
+			action TEXT NOT NULL,
+			installed INTEGER NOT NULL,
+			upgraded INTEGER NOT NULL,
+			removed INTEGER NOT NULL
+		);
+		CREATE TABLE IF NOT EXISTS packages (
+			transaction_id INTEGER NOT NULL,
+			name TEXT NOT NULL,
+			old_version TEXT,
+			new_version TEXT,
+			operation TEXT NOT NULL
+		);",
+	)
+	.map_err(|e| AppError::Other(e.to_string()))?;
+	Ok(conn)
+}
+
+/// Called right after a `handle_*` commits a transaction successfully, so a
+/// failed one (caught earlier by `?`) never shows up in `history`.
+pub fn record(action: &str, parsed: &ParsedOutput) -> AppResult<()> {
+	let conn = open()?;
+	let timestamp = std::time::SystemTime::now()
+	.duration_since(std::time::UNIX_EPOCH)
+	.map(|d| d.as_secs() as i64)
+	.unwrap_or(0);
+
+	conn.execute(
+		"INSERT INTO transactions (timestamp, action, installed, upgraded, removed) VALUES (?1, ?2, ?3, ?4, ?5)",
+		params![timestamp, action, parsed.summary[0], parsed.summary[1], parsed.summary[2]],
+	)
+	.map_err(|e| AppError::Other(e.to_string()))?;
+	let tx_id = conn.last_insert_rowid();
+
+	let insert_pkg = |name: &str, old_version: Option<&str>, new_version: Option<&str>, operation: &str| -> AppResult<()> {
+		conn.execute(
+			"INSERT INTO packages (transaction_id, name, old_version, new_version, operation) VALUES (?1, ?2, ?3, ?4, ?5)",
+			params![tx_id, name, old_version, new_version, operation],
+		)
+		.map_err(|e| AppError::Other(e.to_string()))?;
+		Ok(())
+	};
+
+	for pkg in &parsed.installing {
+		insert_pkg(&pkg.name, None, Some(&pkg.version), "install")?;
+	}
+	for pkg in &parsed.upgrading {
+		insert_pkg(&pkg.name, pkg.old_version.as_deref(), Some(&pkg.version), "upgrade")?;
+	}
+	for pkg in &parsed.removing {
+		insert_pkg(&pkg.name, Some(&pkg.version), None, "remove")?;
+	}
+	Ok(())
+}
+
+pub fn list() -> AppResult<Vec<Transaction>> {
+	let conn = open()?;
+	let mut stmt = conn
+	.prepare("SELECT id, timestamp, action, installed, upgraded, removed FROM transactions ORDER BY id")
+	.map_err(|e| AppError::Other(e.to_string()))?;
+	let rows = stmt
+	.query_map([], |row| {
+		Ok(Transaction {
+			id: row.get(0)?,
+			timestamp: row.get(1)?,
+			action: row.get(2)?,
+			summary: [row.get(3)?, row.get(4)?, row.get(5)?],
+		})
+	})
+	.map_err(|e| AppError::Other(e.to_string()))?;
+
+	let mut out = Vec::new();
+	for row in rows {
+		out.push(row.map_err(|e| AppError::Other(e.to_string()))?);
+	}
+	Ok(out)
+}
+
+pub fn packages_for(tx_id: i64) -> AppResult<Vec<TransactionPackage>> {
+	let conn = open()?;
+	let mut stmt = conn
+	.prepare("SELECT name, old_version, new_version, operation FROM packages WHERE transaction_id = ?1")
+	.map_err(|e| AppError::Other(e.to_string()))?;
+	let rows = stmt
+	.query_map(params![tx_id], |row| {
+		Ok(TransactionPackage {
+			name: row.get(0)?,
+			old_version: row.get(1)?,
+			new_version: row.get(2)?,
+			operation: row.get(3)?,
+		})
+	})
+	.map_err(|e| AppError::Other(e.to_string()))?;
+
+	let mut out = Vec::new();
+	for row in rows {
+		out.push(row.map_err(|e| AppError::Other(e.to_string()))?);
+	}
+	Ok(out)
+}
+
+/// Reshapes a stored package set back into a [`ParsedOutput`] so `history`
+/// can reuse `display_dnf_style` instead of a second rendering path.
+pub fn as_parsed_output(packages: &[TransactionPackage], summary: [i32; 3]) -> ParsedOutput {
+	let mut installing = Vec::new();
+	let mut upgrading = Vec::new();
+	let mut removing = Vec::new();
+
+	for pkg in packages {
+		let version = pkg.new_version.clone().or_else(|| pkg.old_version.clone()).unwrap_or_default();
+		let entry = Package {
+			name: pkg.name.clone(),
+			version,
+			repo: "N/A".to_string(),
+			arch: "unknown".to_string(),
+			old_version: pkg.old_version.clone(),
+		};
+		match pkg.operation.as_str() {
+			"install" => installing.push(entry),
+			"upgrade" => upgrading.push(entry),
+			"remove" => removing.push(entry),
+			_ => {}
+		}
+	}
+
+	ParsedOutput {
+		installing,
+		upgrading,
+		removing,
+		download_size: "0".to_string(),
+		installed_size: "0".to_string(),
+		summary,
+	}
+}
+
+/// Builds the `name[=version]` specs and bare names `undo` needs to reverse
+/// a transaction: freshly installed packages get removed, upgraded ones get
+/// downgraded back via `pkg=old_version`, and removed ones get reinstalled
+/// at the version they were removed at.
+pub fn inverse_ops(packages: &[TransactionPackage]) -> (Vec<String>, Vec<String>) {
+	let mut install_specs = Vec::new();
+	let mut remove_names = Vec::new();
+
+	for pkg in packages {
+		match pkg.operation.as_str() {
+			"install" => remove_names.push(pkg.name.clone()),
+			"upgrade" => {
+				if let Some(old) = &pkg.old_version {
+					install_specs.push(format!("{}={}", pkg.name, old));
+				}
+			}
+			"remove" => match &pkg.old_version {
+				Some(old) => install_specs.push(format!("{}={}", pkg.name, old)),
+				None => install_specs.push(pkg.name.clone()),
+			},
+			_ => {}
+		}
+	}
+
+	(install_specs, remove_names)
+}
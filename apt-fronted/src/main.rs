@@ -1,11 +1,16 @@
+mod history;
+
+use clap::{Parser, Subcommand};
 use colored::*;
 use kdam::{Bar, BarExt, Spinner};
 use regex::Regex;
-use std::env;
-use std::error::Error;
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::os::unix::io::FromRawFd;
+use std::os::unix::process::CommandExt;
 use std::process::{Command, Stdio};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 fn colored(text: &str, color: &str, bold: bool, underline: bool) -> String {
 	let mut s = String::from(text).normal();
@@ -35,6 +40,10 @@ struct Package {
 	version: String,
 	repo: String,
 	arch: String,
+	/// The version being replaced, when known (apt's `Inst` line reports it
+	/// for upgrades). `history::record` stores this so `undo` can downgrade
+	/// back to it instead of just removing the package.
+	old_version: Option<String>,
 }
 
 struct ParsedOutput {
@@ -46,79 +55,398 @@ struct ParsedOutput {
 	summary: [i32; 3], // 0: install, 1: upgrade, 2: remove
 }
 
-fn parse_apt_simulate(output: &str) -> ParsedOutput {
-	let mut installing: Vec<Package> = Vec::new();
-	let mut upgrading: Vec<Package> = Vec::new();
-	let mut removing: Vec<Package> = Vec::new();
-	let mut download_size = "0".to_string();
-	let mut installed_size = "0".to_string();
-	let mut summary = [0, 0, 0];
+/// Resolution of `-y`/`--noconfirm` and `--assume-no`: which way (if any)
+/// `confirm_action` should answer its prompt without touching stdin.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Confirm {
+	Ask,
+	Yes,
+	No,
+}
 
-	let inst_re = Regex::new(r"Inst (\S+) (?:\[(\S+)\] )?\((\S+) ([\S/]+) (?:\[(\S+)\])?\)").unwrap();
-	let remv_re = Regex::new(r"Remv (\S+) \[(\S+)\]").unwrap();
-	let download_re = Regex::new(r"Need to get ([\d.,]+ [kMG]?B) of archives.").unwrap();
-	let installed_re = Regex::new(r"After this operation, ([\d.,]+ [kMG]?B) (?:of additional disk space will be used|disk space will be freed).").unwrap();
-	let summary_re = Regex::new(r"(\d+) (?:packages? )?upgraded, (\d+) newly installed, (\d+) to remove and (\d+) not upgraded.").unwrap();
+/// Covers every way a command invocation or its output can fail, so a
+/// failed download or transaction surfaces instead of being swallowed by a
+/// `let _ = ...` and printed over with "Complete!" regardless.
+#[derive(Debug)]
+enum AppError {
+	Io(io::Error),
+	CommandFailed { args: Vec<String>, code: Option<i32> },
+	ParseError(String),
+	Other(String),
+}
 
-	for line in output.lines() {
-		if let Some(caps) = inst_re.captures(line) {
-			let name = caps[1].to_string();
-			let current_ver = caps.get(2).map_or("".to_string(), |m| m.as_str().to_string());
-			let new_ver = caps[3].to_string();
-			let repo = caps[4].to_string();
-			let arch = caps.get(5).map_or("unknown".to_string(), |m| m.as_str().to_string());
-			let version = if !new_ver.is_empty() { new_ver } else { current_ver.clone() };
-			let pkg = Package {
-				name,
-				version,
-				repo,
-				arch,
-			};
-			if !current_ver.is_empty() {
-				upgrading.push(pkg);
-			} else {
-				installing.push(pkg);
+impl std::fmt::Display for AppError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			AppError::Io(e) => write!(f, "I/O error: {}", e),
+			AppError::CommandFailed { args, code } => match code {
+				Some(c) => write!(f, "command `{}` failed with exit code {}", args.join(" "), c),
+				None => write!(f, "command `{}` was terminated by a signal", args.join(" ")),
+			},
+			AppError::ParseError(msg) => write!(f, "failed to parse command output: {}", msg),
+			AppError::Other(msg) => write!(f, "{}", msg),
+		}
+	}
+}
+
+impl std::error::Error for AppError {}
+
+impl From<io::Error> for AppError {
+	fn from(e: io::Error) -> Self {
+		AppError::Io(e)
+	}
+}
+
+type AppResult<T> = Result<T, AppError>;
+
+/// Encapsulates everything that differs between package managers: how to
+/// build a dry-run/simulate invocation, how to parse that invocation's
+/// output into a [`ParsedOutput`], how to build the real download/commit
+/// invocations, and which stdout patterns mark progress-bar steps. The
+/// `handle_*` functions only ever talk to this trait, never to a hard-coded
+/// `apt`/`pacman`/`dnf` command vector.
+trait PackageBackend {
+	/// Command vector for `sudo <tool> update`-equivalent metadata refresh.
+	fn update_args(&self) -> Vec<String>;
+	/// Command vector for a dry-run of `action` against `packages`.
+	fn simulate_args(&self, action: &str, packages: &[String]) -> Vec<String>;
+	/// Command vector that only fetches archives for `action`, no changes.
+	fn download_args(&self, action: &str, packages: &[String]) -> Vec<String>;
+	/// Command vector that actually commits `action` against `packages`.
+	fn commit_args(&self, action: &str, packages: &[String]) -> Vec<String>;
+	/// Parses a simulate/dry-run transcript into the backend-agnostic
+	/// [`ParsedOutput`] the display layer renders.
+	fn parse_simulation(&self, output: &str) -> ParsedOutput;
+	/// Regexes matched against commit-phase stdout to advance the
+	/// install/remove progress bar by one step per match.
+	fn progress_regexes(&self) -> Vec<Regex>;
+	/// Command vector this backend's cache-cleanup step runs, analogous
+	/// to apt's `autoclean`.
+	fn clean_args(&self) -> Vec<String>;
+
+	/// Extra flag `simulate_args`' own invocation should be run with to
+	/// keep its transcript parseable, if any; only apt's `-s` dry run
+	/// needs one (`-qq`), so the default is "none".
+	fn quiet_flag(&self) -> Option<&'static str> {
+		None
+	}
+
+	/// Command vector that counts how many archives `action` against
+	/// `packages` would fetch, if this backend can report that without a
+	/// real download; `None` means "unknown", which callers treat as 0.
+	fn num_downloads_args(&self, _action: &str, _packages: &[String]) -> Option<Vec<String>> {
+		None
+	}
+
+	/// Runs `cmd_args` to completion while rendering a progress bar, using
+	/// whichever progress channel this backend exposes. The default counts
+	/// `progress_regexes` matches against stdout, one step out of
+	/// `total_steps`; [`AptBackend`] overrides this to read apt/dpkg's
+	/// `Status-Fd` instead, which is locale-independent and gives a real
+	/// percentage rather than a step count.
+	fn execute_with_progress(
+		&self,
+		cmd_args: &[String],
+		desc: String,
+		total_steps: usize,
+		progress_regexes: Vec<Regex>,
+	) -> AppResult<String> {
+		run_with_progress(cmd_args, desc, total_steps, progress_regexes)
+	}
+}
+
+struct AptBackend;
+
+impl PackageBackend for AptBackend {
+	fn update_args(&self) -> Vec<String> {
+		vec!["sudo".to_string(), "apt".to_string(), "update".to_string()]
+	}
+
+	fn simulate_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let mut args: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), action.to_string()];
+		args.extend(packages.iter().cloned());
+		args.push("-s".to_string());
+		args
+	}
+
+	fn download_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let mut args: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), action.to_string(), "-d".to_string(), "-y".to_string()];
+		args.extend(packages.iter().cloned());
+		args
+	}
+
+	fn commit_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let mut args: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), action.to_string(), "-y".to_string(), "--no-download".to_string()];
+		args.extend(packages.iter().cloned());
+		args
+	}
+
+	fn parse_simulation(&self, output: &str) -> ParsedOutput {
+		let mut installing: Vec<Package> = Vec::new();
+		let mut upgrading: Vec<Package> = Vec::new();
+		let mut removing: Vec<Package> = Vec::new();
+		let mut download_size = "0".to_string();
+		let mut installed_size = "0".to_string();
+		let mut summary = [0, 0, 0];
+
+		let inst_re = Regex::new(r"Inst (\S+) (?:\[(\S+)\] )?\((\S+) ([\S/]+) (?:\[(\S+)\])?\)").unwrap();
+		let remv_re = Regex::new(r"Remv (\S+) \[(\S+)\]").unwrap();
+		let download_re = Regex::new(r"Need to get ([\d.,]+ [kMG]?B) of archives.").unwrap();
+		let installed_re = Regex::new(r"After this operation, ([\d.,]+ [kMG]?B) (?:of additional disk space will be used|disk space will be freed).").unwrap();
+		let summary_re = Regex::new(r"(\d+) (?:packages? )?upgraded, (\d+) newly installed, (\d+) to remove and (\d+) not upgraded.").unwrap();
+
+		for line in output.lines() {
+			if let Some(caps) = inst_re.captures(line) {
+				let name = caps[1].to_string();
+				let current_ver = caps.get(2).map_or("".to_string(), |m| m.as_str().to_string());
+				let new_ver = caps[3].to_string();
+				let repo = caps[4].to_string();
+				let arch = caps.get(5).map_or("unknown".to_string(), |m| m.as_str().to_string());
+				let version = if !new_ver.is_empty() { new_ver } else { current_ver.clone() };
+				let old_version = if current_ver.is_empty() { None } else { Some(current_ver.clone()) };
+				let pkg = Package {
+					name,
+					version,
+					repo,
+					arch,
+					old_version,
+				};
+				if !current_ver.is_empty() {
+					upgrading.push(pkg);
+				} else {
+					installing.push(pkg);
+				}
+			} else if let Some(caps) = remv_re.captures(line) {
+				let name = caps[1].to_string();
+				let ver = caps[2].to_string();
+				removing.push(Package {
+					name,
+					version: ver,
+					repo: "N/A".to_string(),
+							  arch: "unknown".to_string(),
+							  old_version: None,
+				});
+			} else if let Some(caps) = download_re.captures(line) {
+				download_size = caps[1].to_string();
+			} else if let Some(caps) = installed_re.captures(line) {
+				installed_size = caps[1].to_string();
+			} else if let Some(caps) = summary_re.captures(line) {
+				let upgrade: i32 = FromStr::from_str(&caps[1]).unwrap_or(0);
+				let install: i32 = FromStr::from_str(&caps[2]).unwrap_or(0);
+				let remove: i32 = FromStr::from_str(&caps[3]).unwrap_or(0);
+				summary = [install, upgrade, remove];
 			}
-		} else if let Some(caps) = remv_re.captures(line) {
-			let name = caps[1].to_string();
-			let ver = caps[2].to_string();
-			removing.push(Package {
-				name,
-				version: ver,
-				repo: "N/A".to_string(),
-						  arch: "unknown".to_string(),
-			});
-		} else if let Some(caps) = download_re.captures(line) {
-			download_size = caps[1].to_string();
-		} else if let Some(caps) = installed_re.captures(line) {
-			installed_size = caps[1].to_string();
-		} else if let Some(caps) = summary_re.captures(line) {
-			let upgrade: i32 = FromStr::from_str(&caps[1]).unwrap_or(0);
-			let install: i32 = FromStr::from_str(&caps[2]).unwrap_or(0);
-			let remove: i32 = FromStr::from_str(&caps[3]).unwrap_or(0);
-			summary = [install, upgrade, remove];
 		}
+
+		ParsedOutput {
+			installing,
+			upgrading,
+			removing,
+			download_size,
+			installed_size,
+			summary,
+		}
+	}
+
+	fn progress_regexes(&self) -> Vec<Regex> {
+		vec![
+			Regex::new(r"^(Unpacking|Rozpakowywanie)").unwrap(),
+			Regex::new(r"^(Setting up|Konfigurowanie)").unwrap(),
+			Regex::new(r"^(Removing|Usuwanie)").unwrap(),
+		]
+	}
+
+	fn clean_args(&self) -> Vec<String> {
+		vec!["sudo".to_string(), "apt".to_string(), "autoclean".to_string()]
+	}
+
+	fn quiet_flag(&self) -> Option<&'static str> {
+		Some("-qq")
+	}
+
+	fn num_downloads_args(&self, action: &str, packages: &[String]) -> Option<Vec<String>> {
+		let mut args: Vec<String> = vec!["apt-get".to_string(), "--print-uris".to_string(), "-y".to_string(), action.to_string()];
+		args.extend(packages.iter().cloned());
+		Some(args)
+	}
+
+	fn execute_with_progress(
+		&self,
+		cmd_args: &[String],
+		desc: String,
+		_total_steps: usize,
+		_progress_regexes: Vec<Regex>,
+	) -> AppResult<String> {
+		run_apt_with_status_fd(cmd_args, desc)
+	}
+}
+
+/// Arch Linux's pacman. `-S`/`-R` take `--print` for a dry run, whose
+/// `resolving dependencies...` transcript is far sparser than apt's, so
+/// only name, version and arch are taken from each package archive name;
+/// the repo is always reported as `pacman` and sizes are not reported.
+struct PacmanBackend;
+
+impl PackageBackend for PacmanBackend {
+	fn update_args(&self) -> Vec<String> {
+		vec!["sudo".to_string(), "pacman".to_string(), "-Sy".to_string()]
+	}
+
+	fn simulate_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let flag = if action == "remove" { "-Rp" } else { "-Sp" };
+		let mut args: Vec<String> = vec!["sudo".to_string(), "pacman".to_string(), flag.to_string()];
+		args.extend(packages.iter().cloned());
+		args
+	}
+
+	fn download_args(&self, _action: &str, packages: &[String]) -> Vec<String> {
+		let mut args: Vec<String> = vec!["sudo".to_string(), "pacman".to_string(), "-Sw".to_string(), "--noconfirm".to_string()];
+		args.extend(packages.iter().cloned());
+		args
+	}
+
+	fn commit_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let flag = if action == "remove" { "-R" } else { "-S" };
+		let mut args: Vec<String> = vec!["sudo".to_string(), "pacman".to_string(), flag.to_string(), "--noconfirm".to_string()];
+		args.extend(packages.iter().cloned());
+		args
+	}
+
+	fn parse_simulation(&self, output: &str) -> ParsedOutput {
+		let pkg_re = Regex::new(r"^(?:[\w.+-]+/)?([\w.+-]+)-([\w.:+-]+)-(\w+)\.pkg\.tar").unwrap();
+		let mut installing = Vec::new();
+		for line in output.lines() {
+			if let Some(caps) = pkg_re.captures(line.trim()) {
+				installing.push(Package {
+					name: caps[1].to_string(),
+					version: caps[2].to_string(),
+					repo: "pacman".to_string(),
+					arch: caps[3].to_string(),
+					old_version: None,
+				});
+			}
+		}
+		let count = installing.len() as i32;
+		ParsedOutput {
+			installing,
+			upgrading: Vec::new(),
+			removing: Vec::new(),
+			download_size: "0".to_string(),
+			installed_size: "0".to_string(),
+			summary: [count, 0, 0],
+		}
+	}
+
+	fn progress_regexes(&self) -> Vec<Regex> {
+		vec![
+			Regex::new(r"^\(\d+/\d+\) installing").unwrap(),
+			Regex::new(r"^\(\d+/\d+\) upgrading").unwrap(),
+			Regex::new(r"^\(\d+/\d+\) removing").unwrap(),
+		]
+	}
+
+	fn clean_args(&self) -> Vec<String> {
+		vec!["sudo".to_string(), "pacman".to_string(), "-Sc".to_string(), "--noconfirm".to_string()]
+	}
+}
+
+/// Fedora's dnf, whose `--assumeno` dry run emits a `Dependencies resolved`
+/// transaction table close enough in shape to apt's that the same
+/// `Install  N Package(s)`-style summary line can be reused.
+struct DnfBackend;
+
+impl PackageBackend for DnfBackend {
+	fn update_args(&self) -> Vec<String> {
+		vec!["sudo".to_string(), "dnf".to_string(), "check-update".to_string()]
+	}
+
+	fn simulate_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let mut args: Vec<String> = vec!["sudo".to_string(), "dnf".to_string(), action.to_string()];
+		args.extend(packages.iter().cloned());
+		args.push("--assumeno".to_string());
+		args
+	}
+
+	fn download_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let mut args: Vec<String> = vec!["sudo".to_string(), "dnf".to_string(), action.to_string(), "--downloadonly".to_string(), "-y".to_string()];
+		args.extend(packages.iter().cloned());
+		args
+	}
+
+	fn commit_args(&self, action: &str, packages: &[String]) -> Vec<String> {
+		let mut args: Vec<String> = vec!["sudo".to_string(), "dnf".to_string(), action.to_string(), "-y".to_string()];
+		args.extend(packages.iter().cloned());
+		args
+	}
+
+	fn parse_simulation(&self, output: &str) -> ParsedOutput {
+		let pkg_re = Regex::new(r"^\s*([\w.+-]+)\s+([\w.+-]+)\s+([\w.:+-]+)\s+(\S+)\s+[\d.,]+ [kMG]?$").unwrap();
+		let summary_re = Regex::new(r"Install\s+(\d+) Package").unwrap();
+		let mut installing = Vec::new();
+		let mut summary = [0, 0, 0];
+		for line in output.lines() {
+			if let Some(caps) = pkg_re.captures(line) {
+				installing.push(Package {
+					name: caps[1].to_string(),
+					arch: caps[2].to_string(),
+					version: caps[3].to_string(),
+					repo: caps[4].to_string(),
+					old_version: None,
+				});
+			} else if let Some(caps) = summary_re.captures(line) {
+				summary[0] = FromStr::from_str(&caps[1]).unwrap_or(0);
+			}
+		}
+		ParsedOutput {
+			installing,
+			upgrading: Vec::new(),
+			removing: Vec::new(),
+			download_size: "0".to_string(),
+			installed_size: "0".to_string(),
+			summary,
+		}
+	}
+
+	fn progress_regexes(&self) -> Vec<Regex> {
+		vec![
+			Regex::new(r"^\s*Installing\s*:").unwrap(),
+			Regex::new(r"^\s*Upgrading\s*:").unwrap(),
+			Regex::new(r"^\s*Removing\s*:").unwrap(),
+		]
+	}
+
+	fn clean_args(&self) -> Vec<String> {
+		vec!["sudo".to_string(), "dnf".to_string(), "clean".to_string(), "packages".to_string()]
 	}
+}
+
+/// Resolves the `--backend <name>` flag (default `apt`) to a concrete
+/// [`PackageBackend`]; unknown names fall back to apt rather than erroring,
+/// since a frontend that refuses to run at all over a typo is worse than
+/// one that guesses the most common backend.
+fn select_backend(name: &str) -> Box<dyn PackageBackend> {
+	match name {
+		"pacman" => Box::new(PacmanBackend),
+		"dnf" => Box::new(DnfBackend),
+		_ => Box::new(AptBackend),
+	}
+}
 
-	ParsedOutput {
-		installing,
-		upgrading,
-		removing,
-		download_size,
-		installed_size,
-		summary,
+/// Builds `backend`'s dry run for `action`/`packages`, appending its
+/// `quiet_flag` (if any) so the transcript stays parseable, and runs it.
+fn run_simulate(backend: &dyn PackageBackend, action: &str, packages: &[String]) -> AppResult<String> {
+	let mut sim_cmd = backend.simulate_args(action, packages);
+	if let Some(flag) = backend.quiet_flag() {
+		sim_cmd.push(flag.to_string());
 	}
+	run_command(&sim_cmd, false)
 }
 
 fn run_command(
 	cmd_args: &[String],
-	simulate: bool,
 	stream: bool,
-) -> Result<String, Box<dyn Error>> {
-	let mut args = cmd_args.to_vec();
-	if simulate {
-		args.push("-qq".to_string());
-	}
+) -> AppResult<String> {
+	let args = cmd_args.to_vec();
 	let mut cmd = Command::new(&args[0]);
 	cmd.args(&args[1..]);
 
@@ -134,7 +462,7 @@ fn run_command(
 		   false
 			)
 			);
-			return Err("Command failed".into());
+			return Err(AppError::CommandFailed { args, code: output.status.code() });
 		}
 		return Ok(String::from_utf8_lossy(&output.stdout).to_string());
 	}
@@ -162,16 +490,17 @@ fn run_command(
 			 false
 		   )
 		);
-		return Err("Command failed".into());
+		return Err(AppError::CommandFailed { args, code: status.code() });
 	}
 
 	Ok(output)
 }
 
-fn get_num_downloads(action: &str, packages: &[String]) -> usize {
-	let mut cmd_args: Vec<String> = vec!["apt-get".to_string(), "--print-uris".to_string(), "-y".to_string(), action.to_string()];
-	cmd_args.extend(packages.iter().cloned());
-	let output = match run_command(&cmd_args, false, false) {
+fn get_num_downloads(backend: &dyn PackageBackend, action: &str, packages: &[String]) -> usize {
+	let Some(cmd_args) = backend.num_downloads_args(action, packages) else {
+		return 0;
+	};
+	let output = match run_command(&cmd_args, false) {
 		Ok(o) => o,
 		Err(_) => return 0,
 	};
@@ -326,7 +655,15 @@ fn color_output(line: &str) -> String {
 		}
 }
 
-fn confirm_action() -> bool {
+/// Prompts for `[y/N]` confirmation, unless `noconfirm`/`--assume-no` made
+/// the answer moot already (so scripts and `hpm`-driven image builds never
+/// block on stdin).
+fn confirm_action(confirm: Confirm) -> bool {
+	match confirm {
+		Confirm::Yes => return true,
+		Confirm::No => return false,
+		Confirm::Ask => {}
+	}
 	loop {
 		print!(
 			"{}",
@@ -346,7 +683,7 @@ fn confirm_action() -> bool {
 	}
 }
 
-fn run_with_progress(cmd_args: &[String], desc: String, total: usize, update_regexes: Vec<Regex>) -> Result<String, Box<dyn Error>> {
+fn run_with_progress(cmd_args: &[String], desc: String, total: usize, update_regexes: Vec<Regex>) -> AppResult<String> {
 	let mut cmd = Command::new(&cmd_args[0]);
 	cmd.args(&cmd_args[1..]);
 	cmd.stdout(Stdio::piped());
@@ -366,8 +703,9 @@ fn run_with_progress(cmd_args: &[String], desc: String, total: usize, update_reg
 		1.0,
 	))
 	.ncols(20u16)
-	.force_refresh(true)
-	.build()?;
+	.force_refresh(io::stdout().is_terminal())
+	.build()
+	.map_err(|e| AppError::Other(e.to_string()))?;
 
 	let mut output = String::new();
 	let mut current: usize = 0;
@@ -382,7 +720,7 @@ fn run_with_progress(cmd_args: &[String], desc: String, total: usize, update_reg
 		for re in &update_regexes {
 			if re.is_match(&line) {
 				current += 1;
-				pb.update_to(current)?;
+				pb.update_to(current).map_err(|e| AppError::Other(e.to_string()))?;
 				break;
 			}
 		}
@@ -391,30 +729,128 @@ fn run_with_progress(cmd_args: &[String], desc: String, total: usize, update_reg
 	let status = child.wait()?;
 	if !status.success() {
 		println!("{}", colored("Error executing command.", "red", false, false));
-		return Err("Command failed".into());
+		return Err(AppError::CommandFailed { args: cmd_args.to_vec(), code: status.code() });
 	}
 
-	pb.set_bar_format("{desc suffix=' '}|{animation}| {count}/{total} [{percentage:.0}%] in {elapsed human=true} ({rate:.1}/s)".to_string())?;
-	pb.clear()?;
-	pb.refresh()?;
+	pb.set_bar_format("{desc suffix=' '}|{animation}| {count}/{total} [{percentage:.0}%] in {elapsed human=true} ({rate:.1}/s)".to_string())
+		.map_err(|e| AppError::Other(e.to_string()))?;
+	pb.clear().map_err(|e| AppError::Other(e.to_string()))?;
+	pb.refresh().map_err(|e| AppError::Other(e.to_string()))?;
 	println!();
 
 	Ok(output)
 }
 
-fn run_download_with_progress(cmd_args: &[String], num_downloads: usize) -> Result<String, Box<dyn Error>> {
-	let get_re = Regex::new(r"^(Get|Pobr):\d+").unwrap();
-	run_with_progress(cmd_args, colored("Downloading", "yellow", false, false), num_downloads, vec![get_re])
-}
+/// Runs an apt/dpkg command with `-o APT::Status-Fd=<fd>` wired to a pipe
+/// so progress comes from apt's machine-readable channel instead of
+/// scraping localized stdout text. Each status line is
+/// `type:pkg:percent:description` (`type` one of `dlstatus`, `pmstatus`,
+/// `pmconffile`, `pmerror`); the description can itself contain colons, so
+/// the split is capped at 4 fields. stdout is still streamed through
+/// `color_output` for the log, it just no longer drives the bar.
+fn run_apt_with_status_fd(cmd_args: &[String], desc: String) -> AppResult<String> {
+	let mut fds: [libc::c_int; 2] = [0, 0];
+	if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+		return Err(AppError::Io(io::Error::last_os_error()));
+	}
+	let (read_fd, write_fd) = (fds[0], fds[1]);
+
+	let mut full_args = cmd_args.to_vec();
+	full_args.push("-o".to_string());
+	full_args.push(format!("APT::Status-Fd={}", write_fd));
+	full_args.push("-o".to_string());
+	full_args.push("Dpkg::Use-Pty=0".to_string());
 
-fn run_install_with_progress(cmd_args: &[String], total_steps: usize) -> Result<String, Box<dyn Error>> {
-	let unpack_re = Regex::new(r"^(Unpacking|Rozpakowywanie)").unwrap();
-	let setup_re = Regex::new(r"^(Setting up|Konfigurowanie)").unwrap();
-	let remove_re = Regex::new(r"^(Removing|Usuwanie)").unwrap();
-	run_with_progress(cmd_args, colored("Transaction", "green", false, false), total_steps, vec![unpack_re, setup_re, remove_re])
+	let mut cmd = Command::new(&full_args[0]);
+	cmd.args(&full_args[1..]);
+	cmd.stdout(Stdio::piped());
+	unsafe {
+		cmd.pre_exec(move || {
+			libc::close(read_fd);
+			Ok(())
+		});
+	}
+
+	let mut child = cmd.spawn()?;
+	unsafe { libc::close(write_fd) };
+	let status_pipe = unsafe { std::fs::File::from_raw_fd(read_fd) };
+
+	let stdout = child.stdout.take().unwrap();
+	let mut scanner = BufReader::new(stdout).lines();
+
+	let pb = Bar::builder()
+	.total(100)
+	.desc(desc)
+	.bar_format("{desc suffix=' '}|{animation}| {spinner} {count}/{total} [{percentage:.0}%] in {elapsed human=true} ({rate:.1}/s, eta: {remaining human=true})".to_string())
+	.spinner(Spinner::new(
+		&["▁▂▃", "▂▃▄", "▃▄▅", "▄▅▆", "▅▆▇", "▆▇█", "▇█▇", "█▇▆", "▇▆▅", "▆▅▄", "▅▄▃", "▄▃▂", "▃▂▁"],
+		30.0,
+		1.0,
+	))
+	.ncols(20u16)
+	.force_refresh(io::stdout().is_terminal())
+	.build()
+	.map_err(|e| AppError::Other(e.to_string()))?;
+	let pb = Arc::new(Mutex::new(pb));
+
+	let status_thread = {
+		let pb = Arc::clone(&pb);
+		thread::spawn(move || {
+			let mut reader = BufReader::new(status_pipe);
+			let mut line = String::new();
+			loop {
+				line.clear();
+				match reader.read_line(&mut line) {
+					Ok(0) | Err(_) => break,
+					Ok(_) => {
+						let fields: Vec<&str> = line.trim_end().splitn(4, ':').collect();
+						if fields.len() >= 3 {
+							if let Ok(percent) = fields[2].parse::<f64>() {
+								if let Ok(mut bar) = pb.lock() {
+									let _ = bar.update_to(percent.clamp(0.0, 100.0) as usize);
+								}
+							}
+						}
+					}
+				}
+			}
+		})
+	};
+
+	let mut output = String::new();
+	while let Some(line) = scanner.next() {
+		let line = line?;
+		let colored_line = color_output(&line);
+		print!("{}", colored_line);
+		output.push_str(&line);
+		output.push('\n');
+	}
+
+	let status = child.wait()?;
+	// The status-fd pipe's write end closes when the child exits, so the
+	// reader thread always reaches EOF on its own; join it rather than
+	// detaching so the bar's last update lands before we finish it.
+	let _ = status_thread.join();
+
+	{
+		let mut bar = pb.lock().unwrap();
+		bar.update_to(100).map_err(|e| AppError::Other(e.to_string()))?;
+		bar.set_bar_format("{desc suffix=' '}|{animation}| {count}/{total} [{percentage:.0}%] in {elapsed human=true} ({rate:.1}/s)".to_string())
+			.map_err(|e| AppError::Other(e.to_string()))?;
+		bar.clear().map_err(|e| AppError::Other(e.to_string()))?;
+		bar.refresh().map_err(|e| AppError::Other(e.to_string()))?;
+	}
+	println!();
+
+	if !status.success() {
+		println!("{}", colored("Error executing command.", "red", false, false));
+		return Err(AppError::CommandFailed { args: cmd_args.to_vec(), code: status.code() });
+	}
+
+	Ok(output)
 }
 
-fn run_command_with_progress(cmd_args: &[String]) -> Result<String, Box<dyn Error>> {
+fn run_command_with_progress(cmd_args: &[String]) -> AppResult<String> {
 	let mut cmd = Command::new(&cmd_args[0]);
 	cmd.args(&cmd_args[1..]);
 	cmd.stdout(Stdio::piped());
@@ -434,8 +870,9 @@ fn run_command_with_progress(cmd_args: &[String]) -> Result<String, Box<dyn Erro
 		1.0,
 	))
 	.ncols(20u16)
-	.force_refresh(true)
-	.build()?;
+	.force_refresh(io::stdout().is_terminal())
+	.build()
+	.map_err(|e| AppError::Other(e.to_string()))?;
 
 	let mut output = String::new();
 
@@ -454,7 +891,7 @@ fn run_command_with_progress(cmd_args: &[String]) -> Result<String, Box<dyn Erro
 				if let Some(pstr) = words.last() {
 					if let Ok(percent) = i32::from_str(pstr) {
 						if percent >= 0 && percent <= 100 {
-							pb.update_to(percent as usize)?;
+							pb.update_to(percent as usize).map_err(|e| AppError::Other(e.to_string()))?;
 						}
 					}
 				}
@@ -465,46 +902,39 @@ fn run_command_with_progress(cmd_args: &[String]) -> Result<String, Box<dyn Erro
 	let status = child.wait()?;
 	if !status.success() {
 		println!("{}", colored("Error executing command.", "red", false, false));
-		return Err("Command failed".into());
+		return Err(AppError::CommandFailed { args: cmd_args.to_vec(), code: status.code() });
 	}
 
-	pb.set_bar_format("{desc suffix=' '}|{animation}| {count}/{total} [{percentage:.0}%] in {elapsed human=true} ({rate:.1}/s)".to_string())?;
-	pb.clear()?;
-	pb.refresh()?;
+	pb.set_bar_format("{desc suffix=' '}|{animation}| {count}/{total} [{percentage:.0}%] in {elapsed human=true} ({rate:.1}/s)".to_string())
+		.map_err(|e| AppError::Other(e.to_string()))?;
+	pb.clear().map_err(|e| AppError::Other(e.to_string()))?;
+	pb.refresh().map_err(|e| AppError::Other(e.to_string()))?;
 	println!();
 
 	Ok(output)
 }
 
-fn handle_install(packages: &[String]) {
+fn handle_install(backend: &dyn PackageBackend, packages: &[String], confirm: Confirm) -> AppResult<()> {
 	if packages.is_empty() {
 		println!(
 			"{}",
 		   colored("No packages specified for install.", "red", false, false)
 		);
-		return;
+		return Ok(());
 	}
 
-	let mut sim_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "install".to_string()];
-	sim_cmd.extend(packages.iter().cloned());
-	sim_cmd.push("-s".to_string());
-
-	let sim_output = match run_command(&sim_cmd, true, false) {
-		Ok(o) => o,
-		Err(_) => return,
-	};
+	let sim_output = run_simulate(backend, "install", packages)?;
 
-	let parsed = parse_apt_simulate(&sim_output);
+	let parsed = backend.parse_simulation(&sim_output);
 	display_dnf_style(&parsed, "install");
 
-	if confirm_action() {
-		let num_downloads = get_num_downloads("install", packages);
+	if confirm_action(confirm) {
+		let num_downloads = get_num_downloads(backend, "install", packages);
 		if num_downloads > 0 && parsed.download_size != "0" {
 			println!("{}", colored("Downloading Packages:", "cyan", true, false));
 			println!("{}", colored("==========================================================================================", "white", false, false));
-			let mut dl_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "install".to_string(), "-d".to_string(), "-y".to_string()];
-			dl_cmd.extend(packages.iter().cloned());
-			let _ = run_download_with_progress(&dl_cmd, num_downloads);
+			let dl_cmd = backend.download_args("install", packages);
+			backend.execute_with_progress(&dl_cmd, colored("Downloading", "yellow", false, false), num_downloads, vec![Regex::new(r"^(Get|Pobr):\d+").unwrap()])?;
 			println!("{}", colored("Complete!", "green", true, false));
 		}
 		println!("{}", colored("Running transaction check", "cyan", true, false));
@@ -514,10 +944,10 @@ fn handle_install(packages: &[String]) {
 		println!("{}", colored("Transaction test succeeded.", "green", false, false));
 		println!("{}", colored("Running transaction", "cyan", true, false));
 		println!("{}", colored("==========================================================================================", "white", false, false));
-		let mut cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "install".to_string(), "-y".to_string(), "--no-download".to_string()];
-		cmd.extend(packages.iter().cloned());
+		let cmd = backend.commit_args("install", packages);
 		let total_steps = (parsed.summary[0] as usize + parsed.summary[1] as usize) * 2 + parsed.summary[2] as usize;
-		let _ = run_install_with_progress(&cmd, total_steps);
+		backend.execute_with_progress(&cmd, colored("Transaction", "green", false, false), total_steps, backend.progress_regexes())?;
+		history::record("install", &parsed)?;
 		println!("{}", colored("Complete!", "green", true, false));
 	} else {
 		println!(
@@ -525,37 +955,30 @@ fn handle_install(packages: &[String]) {
 		   colored("Transaction cancelled.", "yellow", false, false)
 		);
 	}
+	Ok(())
 }
 
-fn handle_remove(packages: &[String]) {
+fn handle_remove(backend: &dyn PackageBackend, packages: &[String], confirm: Confirm) -> AppResult<()> {
 	if packages.is_empty() {
 		println!(
 			"{}",
 		   colored("No packages specified for remove.", "red", false, false)
 		);
-		return;
+		return Ok(());
 	}
 
-	let mut sim_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "remove".to_string()];
-	sim_cmd.extend(packages.iter().cloned());
-	sim_cmd.push("-s".to_string());
-
-	let sim_output = match run_command(&sim_cmd, true, false) {
-		Ok(o) => o,
-		Err(_) => return,
-	};
+	let sim_output = run_simulate(backend, "remove", packages)?;
 
-	let parsed = parse_apt_simulate(&sim_output);
+	let parsed = backend.parse_simulation(&sim_output);
 	display_dnf_style(&parsed, "remove");
 
-	if confirm_action() {
-		let num_downloads = get_num_downloads("remove", packages);
+	if confirm_action(confirm) {
+		let num_downloads = get_num_downloads(backend, "remove", packages);
 		if num_downloads > 0 && parsed.download_size != "0" {
 			println!("{}", colored("Downloading Packages:", "cyan", true, false));
 			println!("{}", colored("==========================================================================================", "white", false, false));
-			let mut dl_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "remove".to_string(), "-d".to_string(), "-y".to_string()];
-			dl_cmd.extend(packages.iter().cloned());
-			let _ = run_download_with_progress(&dl_cmd, num_downloads);
+			let dl_cmd = backend.download_args("remove", packages);
+			backend.execute_with_progress(&dl_cmd, colored("Downloading", "yellow", false, false), num_downloads, vec![Regex::new(r"^(Get|Pobr):\d+").unwrap()])?;
 			println!("{}", colored("Complete!", "green", true, false));
 		}
 		println!("{}", colored("Running transaction check", "cyan", true, false));
@@ -564,10 +987,10 @@ fn handle_remove(packages: &[String]) {
 		println!("{}", colored("Transaction test succeeded.", "green", false, false));
 		println!("{}", colored("Running transaction", "cyan", true, false));
 		println!("{}", colored("==========================================================================================", "white", false, false));
-		let mut cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "remove".to_string(), "-y".to_string(), "--no-download".to_string()];
-		cmd.extend(packages.iter().cloned());
+		let cmd = backend.commit_args("remove", packages);
 		let total_steps = (parsed.summary[0] as usize + parsed.summary[1] as usize) * 2 + parsed.summary[2] as usize;
-		let _ = run_install_with_progress(&cmd, total_steps);
+		backend.execute_with_progress(&cmd, colored("Transaction", "green", false, false), total_steps, backend.progress_regexes())?;
+		history::record("remove", &parsed)?;
 		println!("{}", colored("Complete!", "green", true, false));
 	} else {
 		println!(
@@ -575,29 +998,26 @@ fn handle_remove(packages: &[String]) {
 		   colored("Transaction cancelled.", "yellow", false, false)
 		);
 	}
+	Ok(())
 }
 
-fn handle_update() {
+fn handle_update(backend: &dyn PackageBackend, confirm: Confirm) -> AppResult<()> {
 	println!("{}", colored("Updating package lists...", "cyan", false, false));
-	let update_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "update".to_string()];
-	let _ = run_command_with_progress(&update_cmd);
+	let update_cmd = backend.update_args();
+	run_command_with_progress(&update_cmd)?;
 
-	let sim_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "upgrade".to_string(), "-s".to_string()];
-	let sim_output = match run_command(&sim_cmd, true, false) {
-		Ok(o) => o,
-		Err(_) => return,
-	};
+	let sim_output = run_simulate(backend, "upgrade", &[])?;
 
-	let parsed = parse_apt_simulate(&sim_output);
+	let parsed = backend.parse_simulation(&sim_output);
 	display_dnf_style(&parsed, "upgrade");
 
-	if confirm_action() {
-		let num_downloads = get_num_downloads("upgrade", &[]);
+	if confirm_action(confirm) {
+		let num_downloads = get_num_downloads(backend, "upgrade", &[]);
 		if num_downloads > 0 && parsed.download_size != "0" {
 			println!("{}", colored("Downloading Packages:", "cyan", true, false));
 			println!("{}", colored("==========================================================================================", "white", false, false));
-			let dl_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "upgrade".to_string(), "-d".to_string(), "-y".to_string()];
-			let _ = run_download_with_progress(&dl_cmd, num_downloads);
+			let dl_cmd = backend.download_args("upgrade", &[]);
+			backend.execute_with_progress(&dl_cmd, colored("Downloading", "yellow", false, false), num_downloads, vec![Regex::new(r"^(Get|Pobr):\d+").unwrap()])?;
 			println!("{}", colored("Complete!", "green", true, false));
 		}
 		println!("{}", colored("Running transaction check", "cyan", true, false));
@@ -606,36 +1026,33 @@ fn handle_update() {
 		println!("{}", colored("Transaction test succeeded.", "green", false, false));
 		println!("{}", colored("Running upgrade", "cyan", true, false));
 		println!("{}", colored("==========================================================================================", "white", false, false));
-		let upgrade_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "upgrade".to_string(), "-y".to_string(), "--no-download".to_string()];
+		let upgrade_cmd = backend.commit_args("upgrade", &[]);
 		let total_steps = (parsed.summary[0] as usize + parsed.summary[1] as usize) * 2 + parsed.summary[2] as usize;
-		let _ = run_install_with_progress(&upgrade_cmd, total_steps);
+		backend.execute_with_progress(&upgrade_cmd, colored("Transaction", "green", false, false), total_steps, backend.progress_regexes())?;
+		history::record("upgrade", &parsed)?;
 		println!("{}", colored("Complete!", "green", true, false));
 	} else {
 		println!("{}", colored("Upgrade cancelled.", "yellow", false, false));
 	}
+	Ok(())
 }
 
-fn handle_clean() {
-	let sim_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "autoremove".to_string(), "-s".to_string()];
-	let sim_output = match run_command(&sim_cmd, true, false) {
-		Ok(o) => o,
-		Err(_) => return,
-	};
+fn handle_clean(backend: &dyn PackageBackend, confirm: Confirm) -> AppResult<()> {
+	let sim_output = run_simulate(backend, "autoremove", &[])?;
 
-	let parsed = parse_apt_simulate(&sim_output);
+	let parsed = backend.parse_simulation(&sim_output);
 	display_dnf_style(&parsed, "clean");
 
-	if confirm_action() {
+	if confirm_action(confirm) {
 		println!("{}", colored("Running autoclean", "cyan", false, false));
-		let autoclean_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "autoclean".to_string()];
-		let _ = run_command_with_progress(&autoclean_cmd);
+		run_command_with_progress(&backend.clean_args())?;
 
-		let num_downloads = get_num_downloads("autoremove", &[]);
+		let num_downloads = get_num_downloads(backend, "autoremove", &[]);
 		if num_downloads > 0 && parsed.download_size != "0" {
 			println!("{}", colored("Downloading Packages:", "cyan", true, false));
 			println!("{}", colored("==========================================================================================", "white", false, false));
-			let dl_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "autoremove".to_string(), "-d".to_string(), "-y".to_string()];
-			let _ = run_download_with_progress(&dl_cmd, num_downloads);
+			let dl_cmd = backend.download_args("autoremove", &[]);
+			backend.execute_with_progress(&dl_cmd, colored("Downloading", "yellow", false, false), num_downloads, vec![Regex::new(r"^(Get|Pobr):\d+").unwrap()])?;
 			println!("{}", colored("Complete!", "green", true, false));
 		}
 		println!("{}", colored("Running transaction check", "cyan", true, false));
@@ -644,13 +1061,182 @@ fn handle_clean() {
 		println!("{}", colored("Transaction test succeeded.", "green", false, false));
 		println!("{}", colored("Running autoremove", "cyan", true, false));
 		println!("{}", colored("==========================================================================================", "white", false, false));
-		let autoremove_cmd: Vec<String> = vec!["sudo".to_string(), "apt".to_string(), "autoremove".to_string(), "-y".to_string(), "--no-download".to_string()];
+		let autoremove_cmd = backend.commit_args("autoremove", &[]);
 		let total_steps = (parsed.summary[0] as usize + parsed.summary[1] as usize) * 2 + parsed.summary[2] as usize;
-		let _ = run_install_with_progress(&autoremove_cmd, total_steps);
+		backend.execute_with_progress(&autoremove_cmd, colored("Transaction", "green", false, false), total_steps, backend.progress_regexes())?;
+		history::record("autoremove", &parsed)?;
 		println!("{}", colored("Complete!", "green", true, false));
 	} else {
 		println!("{}", colored("Clean cancelled.", "yellow", false, false));
 	}
+	Ok(())
+}
+
+fn handle_history() -> AppResult<()> {
+	let transactions = history::list()?;
+	if transactions.is_empty() {
+		println!("{}", colored("No recorded transactions.", "yellow", false, false));
+		return Ok(());
+	}
+
+	for tx in &transactions {
+		println!(
+			"\n{} #{} {}",
+			colored("Transaction", "cyan", true, false),
+			tx.id,
+			colored(&format!("({})", tx.action), "white", false, false)
+		);
+		let packages = history::packages_for(tx.id)?;
+		let parsed = history::as_parsed_output(&packages, tx.summary);
+		display_dnf_style(&parsed, &tx.action);
+	}
+	Ok(())
+}
+
+fn handle_undo(id_arg: &str) -> AppResult<()> {
+	let id: i64 = id_arg
+	.parse()
+	.map_err(|_| AppError::Other(format!("'{}' is not a valid transaction id", id_arg)))?;
+
+	let packages = history::packages_for(id)?;
+	if packages.is_empty() {
+		println!("{}", colored("No such transaction.", "red", false, false));
+		return Ok(());
+	}
+
+	let (install_specs, remove_names) = history::inverse_ops(&packages);
+
+	if !install_specs.is_empty() {
+		println!(
+			"{} {}",
+			colored("Reinstalling/downgrading:", "green", true, false),
+			install_specs.join(", ")
+		);
+		let mut cmd = vec!["sudo".to_string(), "apt".to_string(), "install".to_string(), "-y".to_string()];
+		cmd.extend(install_specs);
+		run_command_with_progress(&cmd)?;
+	}
+
+	if !remove_names.is_empty() {
+		println!("{} {}", colored("Removing:", "red", true, false), remove_names.join(", "));
+		let mut cmd = vec!["sudo".to_string(), "apt".to_string(), "remove".to_string(), "-y".to_string()];
+		cmd.extend(remove_names);
+		run_command_with_progress(&cmd)?;
+	}
+
+	println!("{}", colored("Undo complete.", "green", true, false));
+	Ok(())
+}
+
+/// A search hit or `info` lookup's installed-vs-candidate state, mirroring
+/// the fields [`Package`] already tracks for install/remove/upgrade.
+struct PackageInfo {
+	name: String,
+	description: String,
+	installed_version: Option<String>,
+	candidate_version: String,
+}
+
+/// Runs `apt-cache policy <name>` and pulls out the `Installed:`/
+/// `Candidate:` lines; apt-cache prints `(none)` for `Installed:` when the
+/// package isn't on the system, which we fold into `None`.
+fn fetch_policy(name: &str) -> AppResult<(Option<String>, String)> {
+	let cmd = vec!["apt-cache".to_string(), "policy".to_string(), name.to_string()];
+	let output = run_command(&cmd, false)?;
+
+	let installed_re = Regex::new(r"Installed:\s*(\S+)").unwrap();
+	let candidate_re = Regex::new(r"Candidate:\s*(\S+)").unwrap();
+
+	let installed_version = installed_re
+	.captures(&output)
+	.map(|c| c[1].to_string())
+	.filter(|v| v != "(none)");
+	let candidate_version = candidate_re
+	.captures(&output)
+	.map(|c| c[1].to_string())
+	.unwrap_or_else(|| "unknown".to_string());
+
+	Ok((installed_version, candidate_version))
+}
+
+fn print_search_hit(info: &PackageInfo) {
+	let name_label = if info.installed_version.is_some() {
+		colored(&info.name, "green", true, false)
+	} else {
+		colored(&info.name, "cyan", true, false)
+	};
+	println!("{} - {}", name_label, info.description);
+	match &info.installed_version {
+		Some(v) => println!(
+			"    {} {}  {} {}",
+			colored("Installed:", "green", false, false),
+			v,
+			colored("Candidate:", "blue", false, false),
+			info.candidate_version
+		),
+		None => println!("    {} {}", colored("Candidate:", "blue", false, false), info.candidate_version),
+	}
+}
+
+fn handle_search(term: &str) -> AppResult<()> {
+	let cmd = vec!["apt-cache".to_string(), "search".to_string(), term.to_string()];
+	let output = run_command(&cmd, false)?;
+	let hit_re = Regex::new(r"^(\S+) - (.*)$").unwrap();
+
+	let mut found = false;
+	for line in output.lines() {
+		if let Some(caps) = hit_re.captures(line) {
+			found = true;
+			let name = caps[1].to_string();
+			let description = caps[2].to_string();
+			let (installed_version, candidate_version) = fetch_policy(&name)?;
+			print_search_hit(&PackageInfo {
+				name,
+				description,
+				installed_version,
+				candidate_version,
+			});
+		}
+	}
+
+	if !found {
+		println!("{}", colored(&format!("No packages found matching '{}'.", term), "yellow", false, false));
+	}
+	Ok(())
+}
+
+fn handle_info(pkg: &str) -> AppResult<()> {
+	let cmd = vec!["apt-cache".to_string(), "show".to_string(), pkg.to_string()];
+	let output = run_command(&cmd, false)?;
+	if output.trim().is_empty() {
+		println!("{}", colored(&format!("No information found for '{}'.", pkg), "red", false, false));
+		return Ok(());
+	}
+
+	let (installed_version, candidate_version) = fetch_policy(pkg)?;
+	let field_re = Regex::new(r"^([\w-]+):\s*(.*)$").unwrap();
+
+	println!("{}", colored(pkg, "cyan", true, true));
+	for line in output.lines() {
+		if line.trim().is_empty() {
+			// apt-cache show can print multiple version stanzas; only the first is wanted.
+			break;
+		}
+		if let Some(caps) = field_re.captures(line) {
+			let key = &caps[1];
+			if key == "Package" {
+				continue;
+			}
+			println!("{}: {}", colored(key, "yellow", true, false), &caps[2]);
+		}
+	}
+
+	match &installed_version {
+		Some(v) => println!("{}: {}", colored("Installed", "green", true, false), v),
+		None => println!("{}: {}", colored("Installed", "green", true, false), colored("(not installed)", "red", false, false)),
+	}
+	println!("{}: {}", colored("Candidate", "blue", true, false), candidate_version);
+	Ok(())
 }
 
 fn print_help() {
@@ -663,27 +1249,90 @@ fn print_help() {
 		  false
 		)
 	);
-	println!("Usage: apt-frontend <command> [options]");
+	println!("Usage: apt-frontend [--backend apt|pacman|dnf] [-y|--noconfirm|--assume-no] <command> [options]");
 	println!("Commands:");
 	println!(" install <packages...> Install packages");
 	println!(" remove <packages...> Remove packages");
 	println!(" update Update and upgrade packages");
 	println!(" clean Clean up packages");
+	println!(" history List past transactions");
+	println!(" undo <id> Reverse a past transaction");
+	println!(" search <term> Search available packages");
+	println!(" info <package> Show details for a package");
+}
+
+#[derive(Parser)]
+#[command(name = "apt-frontend", about = "Enhanced APT Frontend in DNF Style with Colors and Progress", disable_help_subcommand = true)]
+struct Cli {
+	/// Package manager backend to drive (apt, pacman, dnf); unknown names fall back to apt.
+	#[arg(long, global = true, default_value = "apt")]
+	backend: String,
+
+	/// Assume yes to every prompt, for scripts/CI/unattended image builds.
+	#[arg(short = 'y', long = "noconfirm", global = true)]
+	noconfirm: bool,
+
+	/// Assume no to every prompt instead of yes (cancels rather than proceeds).
+	#[arg(long = "assume-no", global = true)]
+	assume_no: bool,
+
+	#[command(subcommand)]
+	command: Option<Cmd>,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+	/// Install packages
+	Install { packages: Vec<String> },
+	/// Remove packages
+	Remove { packages: Vec<String> },
+	/// Update and upgrade packages
+	Update,
+	/// Clean up packages
+	Clean,
+	/// List past transactions
+	History,
+	/// Reverse a past transaction
+	Undo { id: String },
+	/// Search available packages by name/description
+	Search { term: String },
+	/// Show details for a package
+	Info { package: String },
 }
 
 fn main() {
-	let args: Vec<String> = env::args().collect();
-	if args.len() < 2 {
+	let cli = Cli::parse();
+	let Some(command) = cli.command else {
 		print_help();
 		return;
-	}
+	};
+
+	let backend = select_backend(&cli.backend);
+	let confirm = if cli.noconfirm {
+		Confirm::Yes
+	} else if cli.assume_no {
+		Confirm::No
+	} else {
+		Confirm::Ask
+	};
+
+	let result = match command {
+		Cmd::Install { packages } => handle_install(backend.as_ref(), &packages, confirm),
+		Cmd::Remove { packages } => handle_remove(backend.as_ref(), &packages, confirm),
+		Cmd::Update => handle_update(backend.as_ref(), confirm),
+		Cmd::Clean => handle_clean(backend.as_ref(), confirm),
+		Cmd::History => handle_history(),
+		Cmd::Undo { id } => handle_undo(&id),
+		Cmd::Search { term } => handle_search(&term),
+		Cmd::Info { package } => handle_info(&package),
+	};
 
-	let command = &args[1];
-	match command.as_str() {
-		"install" => handle_install(&args[2..]),
-		"remove" => handle_remove(&args[2..]),
-		"update" => handle_update(),
-		"clean" => handle_clean(),
-		_ => print_help(),
+	if let Err(e) = result {
+		println!("{}", colored(&format!("Error: {}", e), "red", true, false));
+		let code = match &e {
+			AppError::CommandFailed { code: Some(c), .. } => *c,
+			_ => 1,
+		};
+		std::process::exit(code);
 	}
 }
@@ -0,0 +1,125 @@
+//! Root-privileged daemon: owns every mutating operation (install,
+//! remove, update, refresh) so the CLI never needs `sudo` itself. The
+//! CLI talks to this process over D-Bus and renders whatever progress
+//! the daemon streams back.
+use hpm_backend::error::ErrorCode;
+use serde::Serialize;
+use std::future::pending;
+use zbus::{dbus_interface, ConnectionBuilder, SignalContext};
+
+const BUS_NAME: &str = "os.hackeros.hpm.Daemon";
+const OBJECT_PATH: &str = "/os/hackeros/hpm/Daemon";
+
+/// Mirrors `ErrorPayload`/`ErrorCode` so daemon failures serialize back
+/// to the client as structured JSON with a source chain, not a bare
+/// D-Bus error string.
+#[derive(Serialize)]
+struct DaemonError {
+    code: i32,
+    message: String,
+    causes: Vec<String>,
+}
+
+impl DaemonError {
+    fn from_anyhow(code: ErrorCode, err: anyhow::Error) -> DaemonError {
+        DaemonError {
+            code: code as i32,
+            message: err.to_string(),
+            causes: err.chain().skip(1).map(|c| c.to_string()).collect(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("JSON marshal failed")
+    }
+}
+
+struct Daemon;
+
+#[dbus_interface(name = "os.hackeros.hpm.Daemon1")]
+impl Daemon {
+    /// `(package_name, version, path, checksum) -> Ok(()) or a JSON DaemonError`
+    async fn install(
+        &self,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+        package_name: String,
+        version: String,
+        path: String,
+        checksum: String,
+    ) -> Result<(), String> {
+        let result = hpm_backend::install(&package_name, &version, &path, &checksum);
+        Self::progress(&ctx, &package_name, "install", result.is_ok()).await.ok();
+        result.map_err(|e| DaemonError::from_anyhow(ErrorCode::InstallFailed, e).to_json())
+    }
+
+    async fn remove(
+        &self,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+        package_name: String,
+        version: String,
+        path: String,
+    ) -> Result<(), String> {
+        let result = hpm_backend::remove(&package_name, &version, &path);
+        Self::progress(&ctx, &package_name, "remove", result.is_ok()).await.ok();
+        result.map_err(|e| DaemonError::from_anyhow(ErrorCode::RemoveFailed, e).to_json())
+    }
+
+    async fn update(
+        &self,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+        update_list_input: String,
+    ) -> Result<(), String> {
+        let result = hpm_backend::update_list(&update_list_input);
+        Self::progress(&ctx, "update-list", "update", result.is_ok()).await.ok();
+        result.map_err(|e| DaemonError::from_anyhow(ErrorCode::InstallFailed, e).to_json())
+    }
+
+    async fn refresh(&self, #[zbus(signal_context)] ctx: SignalContext<'_>) -> Result<(), String> {
+        let result = hpm_backend::database::open_db()
+            .and_then(|conn| hpm_backend::database::sync_apt_cache(&conn));
+        Self::progress(&ctx, "refresh", "refresh", result.is_ok()).await.ok();
+        result.map_err(|e| DaemonError::from_anyhow(ErrorCode::UnknownCommand, e).to_json())
+    }
+
+    /// Undo the most recently recorded install/remove transaction.
+    async fn rollback(&self, #[zbus(signal_context)] ctx: SignalContext<'_>) -> Result<(), String> {
+        let result = hpm_backend::journal::rollback_last();
+        Self::progress(&ctx, "rollback", "rollback", result.is_ok()).await.ok();
+        result.map_err(|e| DaemonError::from_anyhow(ErrorCode::RollbackFailed, e).to_json())
+    }
+
+    /// Streamed progress/status signal: `(package_name, action, success)`.
+    #[dbus_interface(signal)]
+    async fn progress_signal(
+        ctx: &SignalContext<'_>,
+        package_name: &str,
+        action: &str,
+        success: bool,
+    ) -> zbus::Result<()>;
+}
+
+impl Daemon {
+    async fn progress(
+        ctx: &SignalContext<'_>,
+        package_name: &str,
+        action: &str,
+        success: bool,
+    ) -> zbus::Result<()> {
+        Daemon::progress_signal(ctx, package_name, action, success).await
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let daemon = Daemon;
+    let _conn = ConnectionBuilder::system()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, daemon)?
+        .build()
+        .await?;
+
+    // The connection above owns its own I/O task; park here forever so
+    // the process stays alive to keep serving requests.
+    pending::<()>().await;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! Thin D-Bus client for talking to `hpm-daemon`: every mutating call a
+//! frontend used to run as `sudo backend ...` should go through here
+//! instead, so the frontend process itself never needs root.
+use anyhow::{anyhow, Result};
+use zbus::dbus_proxy;
+
+const BUS_NAME: &str = "os.hackeros.hpm.Daemon";
+const OBJECT_PATH: &str = "/os/hackeros/hpm/Daemon";
+
+#[dbus_proxy(
+    interface = "os.hackeros.hpm.Daemon1",
+    default_service = "os.hackeros.hpm.Daemon",
+    default_path = "/os/hackeros/hpm/Daemon"
+)]
+trait Daemon1 {
+    async fn install(&self, package_name: &str, version: &str, path: &str, checksum: &str) -> zbus::Result<Result<(), String>>;
+    async fn remove(&self, package_name: &str, version: &str, path: &str) -> zbus::Result<Result<(), String>>;
+    async fn update(&self, update_list_input: &str) -> zbus::Result<Result<(), String>>;
+    async fn refresh(&self) -> zbus::Result<Result<(), String>>;
+    async fn rollback(&self) -> zbus::Result<Result<(), String>>;
+
+    #[dbus_proxy(signal)]
+    fn progress_signal(&self, package_name: &str, action: &str, success: bool);
+}
+
+async fn proxy<'a>() -> Result<Daemon1Proxy<'a>> {
+    let conn = zbus::Connection::system().await?;
+    Daemon1Proxy::builder(&conn)
+        .destination(BUS_NAME)?
+        .path(OBJECT_PATH)?
+        .build()
+        .await
+        .map_err(Into::into)
+}
+
+fn unwrap_daemon_error(result: Result<(), String>) -> Result<()> {
+    result.map_err(|json| anyhow!("daemon call failed: {}", json))
+}
+
+pub async fn install(package_name: &str, version: &str, path: &str, checksum: &str) -> Result<()> {
+    let p = proxy().await?;
+    unwrap_daemon_error(p.install(package_name, version, path, checksum).await?)
+}
+
+pub async fn remove(package_name: &str, version: &str, path: &str) -> Result<()> {
+    let p = proxy().await?;
+    unwrap_daemon_error(p.remove(package_name, version, path).await?)
+}
+
+pub async fn update(update_list_input: &str) -> Result<()> {
+    let p = proxy().await?;
+    unwrap_daemon_error(p.update(update_list_input).await?)
+}
+
+pub async fn refresh() -> Result<()> {
+    let p = proxy().await?;
+    unwrap_daemon_error(p.refresh().await?)
+}
+
+pub async fn rollback() -> Result<()> {
+    let p = proxy().await?;
+    unwrap_daemon_error(p.rollback().await?)
+}
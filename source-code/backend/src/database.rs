@@ -0,0 +1,219 @@
+use crate::manifest::Manifest;
+use crate::state::{load_state, save_state};
+use anyhow::{Context, Result};
+use colored::*;
+use rusqlite::{params, Connection};
+use std::process::Command;
+
+const DB_PATH: &str = "/var/lib/hpm/packages.db";
+
+/// A single row of the `packages` table, covering both native `.hk`
+/// packages and packages known to apt.
+#[derive(Debug)]
+pub struct DbPackage {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub depends: String,
+    pub make_depends: String,
+    pub checksum: String,
+    pub source: String,
+}
+
+pub fn open_db() -> Result<Connection> {
+    if let Some(parent) = std::path::Path::new(DB_PATH).parent() {
+        std::fs::create_dir_all(parent).context("Failed to create database directory")?;
+    }
+    let conn = Connection::open(DB_PATH).context("Failed to open package database")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS packages (
+            name TEXT NOT NULL,
+            version TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            depends TEXT NOT NULL DEFAULT '',
+            make_depends TEXT NOT NULL DEFAULT '',
+            checksum TEXT NOT NULL DEFAULT '',
+            source TEXT NOT NULL DEFAULT 'hk',
+            PRIMARY KEY (name, source)
+        )",
+        [],
+    )
+    .context("Failed to create packages table")?;
+    Ok(conn)
+}
+
+/// Insert or update the row for a single package.
+pub fn upsert_package(conn: &Connection, pkg: &DbPackage) -> Result<()> {
+    conn.execute(
+        "INSERT INTO packages (name, version, description, depends, make_depends, checksum, source)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(name, source) DO UPDATE SET
+            version = excluded.version,
+            description = excluded.description,
+            depends = excluded.depends,
+            make_depends = excluded.make_depends,
+            checksum = excluded.checksum",
+        params![
+            pkg.name,
+            pkg.version,
+            pkg.description,
+            pkg.depends,
+            pkg.make_depends,
+            pkg.checksum,
+            pkg.source
+        ],
+    )
+    .context("Failed to upsert package row")?;
+    Ok(())
+}
+
+/// Record an installed `.hk` package (invoked alongside `update_state`).
+pub fn record_manifest(manifest: &Manifest, checksum: &str) -> Result<()> {
+    let conn = open_db()?;
+    let depends = manifest
+        .deps
+        .iter()
+        .map(|(k, v)| format!("{} {}", k, v))
+        .collect::<Vec<_>>()
+        .join(", ");
+    upsert_package(
+        &conn,
+        &DbPackage {
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            description: manifest.summary.clone(),
+            depends,
+            make_depends: String::new(),
+            checksum: checksum.to_string(),
+            source: "hk".to_string(),
+        },
+    )
+}
+
+/// Refresh the apt-sourced rows from `apt-cache`, best-effort: a missing
+/// `apt-cache` binary or empty output just leaves the hk rows untouched.
+pub fn sync_apt_cache(conn: &Connection) -> Result<()> {
+    let output = Command::new("apt-cache").arg("dumpavail").output();
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return Ok(()),
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    let mut name = String::new();
+    let mut version = String::new();
+    let mut description = String::new();
+    let mut depends = String::new();
+
+    let flush = |conn: &Connection, name: &str, version: &str, description: &str, depends: &str| -> Result<()> {
+        if name.is_empty() {
+            return Ok(());
+        }
+        upsert_package(
+            conn,
+            &DbPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                description: description.to_string(),
+                depends: depends.to_string(),
+                make_depends: String::new(),
+                checksum: String::new(),
+                source: "apt".to_string(),
+            },
+        )
+    };
+
+    for line in text.lines() {
+        if line.is_empty() {
+            flush(conn, &name, &version, &description, &depends)?;
+            name.clear();
+            version.clear();
+            description.clear();
+            depends.clear();
+            continue;
+        }
+        if let Some(v) = line.strip_prefix("Package: ") {
+            name = v.trim().to_string();
+        } else if let Some(v) = line.strip_prefix("Version: ") {
+            version = v.trim().to_string();
+        } else if let Some(v) = line.strip_prefix("Description: ") {
+            description = v.trim().to_string();
+        } else if let Some(v) = line.strip_prefix("Depends: ") {
+            depends = v.trim().to_string();
+        }
+    }
+    flush(conn, &name, &version, &description, &depends)?;
+
+    Ok(())
+}
+
+/// Run a `LIKE`-based search across name + description and print
+/// colorized results, distinguishing `.hk` packages from apt packages.
+pub fn search(query: &str) -> Result<()> {
+    let conn = open_db()?;
+    sync_apt_cache(&conn).ok();
+
+    let like = format!("%{}%", query);
+    let mut stmt = conn.prepare(
+        "SELECT name, version, description, source FROM packages
+         WHERE name LIKE ?1 OR description LIKE ?1
+         ORDER BY source, name",
+    )?;
+    let rows = stmt.query_map(params![like], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+
+    let mut found = false;
+    for row in rows {
+        let (name, version, description, source) = row?;
+        found = true;
+        let tag = if source == "hk" {
+            "[hk]".green()
+        } else {
+            "[apt]".blue()
+        };
+        println!(
+            "{} {} {} - {}",
+            tag,
+            name.bold(),
+            version.yellow(),
+            description
+        );
+    }
+
+    if !found {
+        println!("{}", "No packages found.".red());
+    }
+
+    Ok(())
+}
+
+/// Compatibility shim: mirror the `packages` table into the legacy
+/// `State` shape so `update_state`/`load_state` callers keep working.
+pub fn sync_state_from_db() -> Result<()> {
+    let conn = open_db()?;
+    let mut stmt = conn.prepare("SELECT name, version, checksum FROM packages WHERE source = 'hk'")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+
+    let mut state = load_state()?;
+    for row in rows {
+        let (name, version, checksum) = row?;
+        state
+            .packages
+            .entry(name)
+            .or_default()
+            .insert(version, checksum);
+    }
+    save_state(&state)
+}
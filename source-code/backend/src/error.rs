@@ -21,6 +21,8 @@ pub enum ErrorCode {
     InstallFailed = 4,
     RemoveFailed = 5,
     VerificationFailed = 6,
+    RollbackFailed = 7,
+    SandboxSetupFailed = 8,
     UnknownCommand = 99,
 }
 
@@ -0,0 +1,174 @@
+//! Post-install/pre-remove system side effects (man pages, GSettings
+//! schemas, info pages, post-install scripts, users/groups, `/etc/shells`).
+//! These run outside the landlock sandbox since, unlike `install_commands`,
+//! they mutate host state rather than the package's own tree.
+use crate::manifest::{GroupHook, Hooks, UserHook};
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::process::Command;
+
+const SHELLS_PATH: &str = "/etc/shells";
+
+impl Hooks {
+    /// Runs every requested hook in dependency order: groups and users
+    /// first, since a pinstall script or a later hook category may expect
+    /// the account to already exist; pinstall scripts run last, after
+    /// everything else they might depend on is in place.
+    pub fn run_install(&self) -> Result<()> {
+        for group in &self.groups {
+            create_group(group).with_context(|| format!("Failed to create group '{}'", group.name))?;
+        }
+        for user in &self.users {
+            create_user(user).with_context(|| format!("Failed to create user '{}'", user.name))?;
+        }
+        if self.man {
+            run_mandb().context("Failed to update man-db")?;
+        }
+        if self.glib_schema {
+            compile_glib_schemas().context("Failed to compile GSettings schemas")?;
+        }
+        for page in &self.info {
+            install_info_page(page).with_context(|| format!("Failed to register info page '{}'", page))?;
+        }
+        for shell in &self.shells {
+            add_shell(shell).with_context(|| format!("Failed to add '{}' to /etc/shells", shell))?;
+        }
+        for script in &self.pinstall {
+            run_pinstall(script).with_context(|| format!("Post-install script failed: {}", script))?;
+        }
+        Ok(())
+    }
+
+    /// Undoes `run_install`'s reversible side effects in the opposite
+    /// order. Users/groups are only deleted when `purge_users` opts in;
+    /// otherwise the accounts are left behind, since files still owned by
+    /// a deleted uid are worse than an idle system account.
+    pub fn run_remove(&self) -> Result<()> {
+        for shell in &self.shells {
+            remove_shell(shell).with_context(|| format!("Failed to remove '{}' from /etc/shells", shell))?;
+        }
+        for page in &self.info {
+            remove_info_page(page).with_context(|| format!("Failed to unregister info page '{}'", page))?;
+        }
+        if self.glib_schema {
+            compile_glib_schemas().context("Failed to recompile GSettings schemas")?;
+        }
+        if self.man {
+            run_mandb().context("Failed to update man-db")?;
+        }
+        if self.purge_users {
+            for user in &self.users {
+                remove_user(&user.name).with_context(|| format!("Failed to remove user '{}'", user.name))?;
+            }
+            for group in &self.groups {
+                remove_group(&group.name).with_context(|| format!("Failed to remove group '{}'", group.name))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn group_exists(name: &str) -> bool {
+    Command::new("getent").arg("group").arg(name).status().map(|s| s.success()).unwrap_or(false)
+}
+
+fn user_exists(name: &str) -> bool {
+    Command::new("getent").arg("passwd").arg(name).status().map(|s| s.success()).unwrap_or(false)
+}
+
+fn create_group(group: &GroupHook) -> Result<()> {
+    if group_exists(&group.name) {
+        return Ok(());
+    }
+    let mut cmd = Command::new("groupadd");
+    if let Some(gid) = &group.gid {
+        cmd.arg("-g").arg(gid);
+    }
+    cmd.arg(&group.name);
+    run(&mut cmd)
+}
+
+fn remove_group(name: &str) -> Result<()> {
+    if !group_exists(name) {
+        return Ok(());
+    }
+    run(Command::new("groupdel").arg(name))
+}
+
+fn create_user(user: &UserHook) -> Result<()> {
+    if user_exists(&user.name) {
+        return Ok(());
+    }
+    let mut cmd = Command::new("useradd");
+    cmd.arg("--system").arg("--no-create-home");
+    if let Some(uid) = &user.uid {
+        cmd.arg("-u").arg(uid);
+    }
+    if let Some(shell) = &user.shell {
+        cmd.arg("-s").arg(shell);
+    }
+    cmd.arg(&user.name);
+    run(&mut cmd)
+}
+
+fn remove_user(name: &str) -> Result<()> {
+    if !user_exists(name) {
+        return Ok(());
+    }
+    run(Command::new("userdel").arg(name))
+}
+
+fn run_mandb() -> Result<()> {
+    run(Command::new("mandb").arg("-q"))
+}
+
+fn compile_glib_schemas() -> Result<()> {
+    run(Command::new("glib-compile-schemas").arg("/usr/share/glib-2.0/schemas"))
+}
+
+fn install_info_page(page: &str) -> Result<()> {
+    run(Command::new("install-info").arg(page).arg("/usr/share/info/dir"))
+}
+
+fn remove_info_page(page: &str) -> Result<()> {
+    run(Command::new("install-info").arg("--remove").arg(page).arg("/usr/share/info/dir"))
+}
+
+fn run_pinstall(script: &str) -> Result<()> {
+    run(Command::new("/bin/sh").arg("-c").arg(script))
+}
+
+/// Adds `shell` to `/etc/shells` if it isn't already listed.
+fn add_shell(shell: &str) -> Result<()> {
+    let existing = fs::read_to_string(SHELLS_PATH).unwrap_or_default();
+    if existing.lines().any(|line| line == shell) {
+        return Ok(());
+    }
+    let mut updated = existing;
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(shell);
+    updated.push('\n');
+    fs::write(SHELLS_PATH, updated).context("Failed to write /etc/shells")
+}
+
+/// Strips `shell` from `/etc/shells`; a missing file is treated as
+/// already-clean rather than an error.
+fn remove_shell(shell: &str) -> Result<()> {
+    let existing = match fs::read_to_string(SHELLS_PATH) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(()),
+    };
+    let updated: String = existing.lines().filter(|line| *line != shell).map(|line| format!("{}\n", line)).collect();
+    fs::write(SHELLS_PATH, updated).context("Failed to write /etc/shells")
+}
+
+fn run(cmd: &mut Command) -> Result<()> {
+    let program = format!("{:?}", cmd.get_program());
+    let status = cmd.status().with_context(|| format!("Failed to execute {}", program))?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Transaction journal for `install`/`remove`: snapshots `State` before a
+//! change goes out so a failed step can be rolled back, and keeps
+//! completed snapshots around under [`JOURNAL_DIR`] as history.
+use crate::state::{load_state, save_state, State};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const JOURNAL_DIR: &str = "/var/lib/hpm/journal";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Transaction {
+    pub timestamp: String,
+    pub action: String,
+    pub packages: Vec<String>,
+    pub state_before: State,
+}
+
+/// Snapshot `State` as it is right now, before `action` is applied to
+/// `packages`. Call [`write`] once the transaction is ready to be
+/// recorded (before the mutating call, so a crash mid-install still
+/// leaves a journal entry to roll back to).
+pub fn begin(action: &str, packages: &[String]) -> Result<Transaction> {
+    Ok(Transaction {
+        timestamp: timestamp_name()?,
+        action: action.to_string(),
+        packages: packages.to_vec(),
+        state_before: load_state()?,
+    })
+}
+
+/// Persist `tx` to `JOURNAL_DIR/<timestamp>.json`, using the same
+/// tmp-file-then-rename pattern as `state::save_state` so a reader never
+/// observes a half-written journal entry.
+pub fn write(tx: &Transaction) -> Result<PathBuf> {
+    fs::create_dir_all(JOURNAL_DIR).context("Failed to create journal directory")?;
+    let path = PathBuf::from(JOURNAL_DIR).join(format!("{}.json", tx.timestamp));
+    let tmp_path = format!("{}.tmp", path.display());
+    let data = serde_json::to_vec_pretty(tx)?;
+    fs::write(&tmp_path, data).context("Failed to write journal entry")?;
+    fs::rename(&tmp_path, &path).context("Failed to finalize journal entry")?;
+    Ok(path)
+}
+
+/// Journal entries oldest to newest, the filenames already sorting in
+/// timestamp order.
+pub fn list() -> Result<Vec<PathBuf>> {
+    if !Path::new(JOURNAL_DIR).exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(JOURNAL_DIR)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+fn load(path: &Path) -> Result<Transaction> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_slice(&data).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Every recorded transaction, oldest to newest, for `Commands::History`.
+pub fn history() -> Result<Vec<Transaction>> {
+    list()?.iter().map(|path| load(path)).collect()
+}
+
+/// Undo the most recently recorded transaction: packages present now but
+/// absent from the snapshot are freshly-installed, so their store
+/// directory is removed; packages the snapshot remembers but that are
+/// now missing were removed by the transaction and can't be restored
+/// from nothing, so only their state record comes back and a warning is
+/// printed. `state.json` is then restored from the snapshot and the
+/// journal entry is consumed.
+pub fn rollback_last() -> Result<()> {
+    let entries = list()?;
+    let path = entries.last().ok_or_else(|| anyhow!("No transaction to roll back"))?;
+    let tx = load(path)?;
+    let current = load_state()?;
+
+    for (name, versions) in &current.packages {
+        let before_versions = tx.state_before.packages.get(name);
+        for version in versions.keys() {
+            let existed_before = before_versions.map(|v| v.contains_key(version)).unwrap_or(false);
+            if !existed_before {
+                let store_path = format!("{}{}/{}", crate::STORE_PATH, name, version);
+                let _ = fs::remove_dir_all(&store_path);
+            }
+        }
+    }
+
+    for (name, before_versions) in &tx.state_before.packages {
+        if !current.packages.contains_key(name) {
+            eprintln!(
+                "warning: '{}' was removed by this transaction and can't be reinstalled from the journal alone; restoring its state record only ({} version(s))",
+                name,
+                before_versions.len()
+            );
+        }
+    }
+
+    save_state(&tx.state_before)?;
+    fs::remove_file(path).context("Failed to remove consumed journal entry")?;
+    Ok(())
+}
+
+fn timestamp_name() -> Result<String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("System clock is before the epoch")?;
+    Ok(format!("{:020}", now.as_nanos()))
+}
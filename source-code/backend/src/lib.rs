@@ -0,0 +1,203 @@
+pub mod client;
+pub mod database;
+pub mod error;
+pub mod hooks;
+pub mod journal;
+pub mod manifest;
+pub mod modules;
+pub mod resolver;
+pub mod sandbox;
+pub mod state;
+pub mod store;
+pub mod verify;
+
+use anyhow::{Context, Result};
+use manifest::Manifest;
+use sandbox::setup_sandbox;
+use state::{load_state, save_state};
+use std::fs;
+use std::path::Path;
+
+pub const STORE_PATH: &str = "/usr/lib/HackerOS/hpm/store/";
+
+pub fn install(package_name: &str, version: &str, path: &str, checksum: &str) -> Result<()> {
+    let tx = journal::begin("install", &[package_name.to_string()])?;
+    journal::write(&tx).context("Failed to record transaction journal")?;
+
+    match install_inner(package_name, version, path, checksum) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            journal::rollback_last().context("Install failed and rollback also failed")?;
+            Err(e)
+        }
+    }
+}
+
+fn install_inner(package_name: &str, version: &str, path: &str, checksum: &str) -> Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    fs::create_dir_all(&tmp_path).context("Failed to create tmp directory")?;
+
+    let contents_path = format!("{}/contents", &tmp_path);
+    if Path::new(&contents_path).exists() {
+        for entry in fs::read_dir(&contents_path)? {
+            let entry = entry?;
+            let old_p = entry.path();
+            let file_name = entry.file_name();
+            let new_p = Path::new(&tmp_path).join(file_name);
+            fs::rename(&old_p, &new_p).context("Move failed")?;
+        }
+        fs::remove_dir(&contents_path).context("Remove contents dir failed")?;
+    }
+
+    let manifest = Manifest::load_info(&tmp_path)?;
+
+    if !manifest.deps.is_empty() {
+        let order = resolver::resolve(package_name, &manifest, STORE_PATH)?;
+        let state = load_state()?;
+        for (dep_name, dep_version) in &order {
+            let satisfied = state
+                .packages
+                .get(dep_name)
+                .map(|versions| versions.contains_key(dep_version))
+                .unwrap_or(false);
+            if !satisfied {
+                install_dependency(dep_name, dep_version)
+                    .with_context(|| format!("Failed to install dependency {} {}", dep_name, dep_version))?;
+            }
+        }
+    }
+
+    setup_sandbox(&tmp_path, &manifest, true, None, vec![], false).context("Sandbox setup failed")?;
+
+    let file_manifest = verify::verify_tree(&tmp_path, checksum)?;
+
+    let object_hashes = store::materialize(Path::new(&tmp_path), Path::new(path), &file_manifest)
+        .context("Failed to materialize package into the object store")?;
+
+    manifest.hooks.run_install().context("Post-install hook failed")?;
+
+    update_state(package_name, version, checksum, &manifest, &object_hashes)?;
+
+    Ok(())
+}
+
+/// Install an already-unpacked dependency found by the resolver at
+/// `STORE_PATH/<name>/<version>`: run its sandboxed install commands and
+/// record it, skipping the download/rename dance `install()` needs for
+/// the originally requested package.
+fn install_dependency(name: &str, version: &str) -> Result<()> {
+    let path = format!("{}{}/{}", STORE_PATH, name, version);
+    install_unpacked(&path)
+}
+
+/// Install a package that's already unpacked on disk at `path` (a local
+/// `.hk` directory, not yet checksummed against anything): run its
+/// `install_commands` confined by `manifest.sandbox`, then hash the
+/// result and record it. `manifest.bins` is what `remove_inner` walks to
+/// undo them, so nothing further needs tracking here.
+fn install_unpacked(path: &str) -> Result<()> {
+    let manifest = Manifest::load_info(path)?;
+    setup_sandbox(path, &manifest, true, None, vec![], false).context("Sandbox setup failed")?;
+    let file_manifest = verify::build_manifest(Path::new(path))?;
+    let checksum = verify::root_digest(&file_manifest);
+    let object_hashes = store::materialize_in_place(Path::new(path), &file_manifest)
+        .context("Failed to deduplicate package into the object store")?;
+    manifest.hooks.run_install().context("Post-install hook failed")?;
+    update_state(&manifest.name, &manifest.version, &checksum, &manifest, &object_hashes)
+}
+
+/// Entry point for installing a local, already-unpacked `.hk` package
+/// directory straight from disk, as opposed to [`install`]'s
+/// download-then-verify-a-known-checksum flow.
+pub fn install_local(path: &str) -> Result<()> {
+    let manifest = Manifest::load_info(path)?;
+    let tx = journal::begin("install", &[manifest.name.clone()])?;
+    journal::write(&tx).context("Failed to record transaction journal")?;
+
+    match install_unpacked(path) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            journal::rollback_last().context("Install failed and rollback also failed")?;
+            Err(e)
+        }
+    }
+}
+
+pub fn remove(package_name: &str, version: &str, path: &str) -> Result<()> {
+    let tx = journal::begin("remove", &[package_name.to_string()])?;
+    journal::write(&tx).context("Failed to record transaction journal")?;
+
+    match remove_inner(package_name, version, path) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            journal::rollback_last().context("Remove failed and rollback also failed")?;
+            Err(e)
+        }
+    }
+}
+
+fn remove_inner(package_name: &str, version: &str, path: &str) -> Result<()> {
+    let manifest = Manifest::load_info(path)?;
+
+    manifest.hooks.run_remove().context("Pre-remove hook failed")?;
+
+    for bin in &manifest.bins {
+        let bin_path = format!("/usr/bin/{}", bin);
+        let _ = fs::remove_file(&bin_path);
+    }
+
+    fs::remove_dir_all(path).context("Delete tree failed")?;
+
+    let mut state = load_state()?;
+    if let Some(vers) = state.packages.get_mut(package_name) {
+        vers.remove(version);
+        if vers.is_empty() {
+            state.packages.remove(package_name);
+        }
+    }
+    let freed_objects = state::release_objects(&mut state, package_name, version);
+    save_state(&state)?;
+    store::unlink_objects(&freed_objects).context("Failed to unlink freed objects")?;
+
+    Ok(())
+}
+
+/// Sweeps `STORE_PATH/objects/` for blobs the recorded refcounts no longer
+/// reference and removes them; covers objects orphaned by a crash between
+/// a rename into the pool and the state save meant to record it.
+pub fn gc() -> Result<usize> {
+    let state = load_state()?;
+    store::gc(&state.object_refcounts)
+}
+
+pub fn run(args: &[String]) -> Result<()> {
+    let package_name = &args[0];
+    let bin = &args[1];
+    let extra_args = args[2..].to_vec();
+
+    let path = format!("{}{}/current", STORE_PATH, package_name);
+
+    let manifest = Manifest::load_info(&path)?;
+
+    setup_sandbox(&path, &manifest, false, Some(bin), extra_args, false)?;
+
+    Ok(())
+}
+
+/// Update both the legacy `state.json` and the `packages` database so the
+/// two stay in sync; `load_state`/`save_state` remain the compatibility
+/// shim described in the database module.
+pub fn update_state(package_name: &str, version: &str, checksum: &str, manifest: &Manifest, object_hashes: &[String]) -> Result<()> {
+    state::update_state(package_name, version, checksum, object_hashes)?;
+    database::record_manifest(manifest, checksum)
+}
+
+/// Reads the `<action>\t<name>\t<version>\t<type>` update-list protocol
+/// (`type` optional, defaulting to `apt`) from `input` and applies it
+/// through the `ModuleRegistry`, batching operations per backend so one
+/// that supports `update_list` (like apt) runs as a single transaction.
+pub fn update_list(input: &str) -> Result<()> {
+    let ops = modules::parse_update_list(input)?;
+    let registry = modules::ModuleRegistry::new(STORE_PATH);
+    registry.apply_batch(&ops)
+}
@@ -14,6 +14,7 @@ pub struct Manifest {
     pub bins: Vec<String>,
     pub sandbox: Sandbox,
     pub install_commands: Vec<String>,
+    pub hooks: Hooks,
 }
 
 #[derive(Debug)]
@@ -22,6 +23,56 @@ pub struct Sandbox {
     pub filesystem: Vec<String>,
     pub gui: bool,
     pub dev: bool,
+    pub seccomp: SeccompPolicy,
+    /// Linux capability names (e.g. `CAP_NET_BIND_SERVICE`) kept in the
+    /// child's permitted/effective/bounding sets; every other capability
+    /// is dropped before `restrict_self()`. Empty by default.
+    pub capabilities: Vec<String>,
+}
+
+/// `[sandbox] seccomp` knob controlling the syscall filter `setup_seccomp`
+/// installs: `Strict` default-denies everything except an allowlist of
+/// syscalls common installers and `/bin/sh` need; `Relaxed` default-allows
+/// and only denies a known-dangerous set. `Relaxed` is the default since a
+/// hand-picked allowlist is easy to make too narrow for an arbitrary
+/// package's install script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeccompPolicy {
+    Strict,
+    #[default]
+    Relaxed,
+}
+
+/// Post-install/pre-remove system side effects a package can ask for, run
+/// outside the landlock sandbox by [`crate::hooks`] since they mutate host
+/// state (man pages, GSettings schemas, users, groups, `/etc/shells`).
+#[derive(Debug, Default)]
+pub struct Hooks {
+    pub man: bool,
+    pub glib_schema: bool,
+    pub info: Vec<String>,
+    pub pinstall: Vec<String>,
+    pub users: Vec<UserHook>,
+    pub groups: Vec<GroupHook>,
+    pub shells: Vec<String>,
+    /// Whether `run_remove` deletes the users/groups `run_install` created,
+    /// as opposed to leaving the accounts in place. Defaults to `false`
+    /// since orphaned files owned by a removed uid are a worse outcome
+    /// than an unused system account.
+    pub purge_users: bool,
+}
+
+#[derive(Debug)]
+pub struct UserHook {
+    pub name: String,
+    pub uid: Option<String>,
+    pub shell: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct GroupHook {
+    pub name: String,
+    pub gid: Option<String>,
 }
 
 impl Manifest {
@@ -106,6 +157,19 @@ impl Manifest {
         .unwrap_or(false);
         let gui = sandbox_sec.get("gui").and_then(|v| v.as_bool().ok()).unwrap_or(false);
         let dev = sandbox_sec.get("dev").and_then(|v| v.as_bool().ok()).unwrap_or(false);
+        let seccomp = match sandbox_sec.get("seccomp").and_then(|v| v.as_string().ok()).as_deref() {
+            Some("strict") => SeccompPolicy::Strict,
+            _ => SeccompPolicy::Relaxed,
+        };
+        let capabilities_map = sandbox_sec.get("capabilities").and_then(|v| v.as_map().ok());
+        let mut capabilities = Vec::new();
+        if let Some(cm) = capabilities_map {
+            for (k, v) in cm {
+                if v.as_string().map_err(|_| anyhow!("Invalid capability value"))? == "" {
+                    capabilities.push(k.clone());
+                }
+            }
+        }
         let fs_map = sandbox_sec.get("filesystem").and_then(|v| v.as_map().ok());
         let mut filesystem = Vec::new();
         if let Some(fm) = fs_map {
@@ -126,6 +190,72 @@ impl Manifest {
                 }
             }
         }
+        let hooks_sec = config.get("hooks").and_then(|v| v.as_map().ok());
+        let man = hooks_sec
+        .and_then(|h| h.get("man"))
+        .and_then(|v| v.as_bool().ok())
+        .unwrap_or(false);
+        let glib_schema = hooks_sec
+        .and_then(|h| h.get("glib_schema"))
+        .and_then(|v| v.as_bool().ok())
+        .unwrap_or(false);
+        let purge_users = hooks_sec
+        .and_then(|h| h.get("purge_users"))
+        .and_then(|v| v.as_bool().ok())
+        .unwrap_or(false);
+        let info_map = hooks_sec.and_then(|h| h.get("info")).and_then(|v| v.as_map().ok());
+        let mut info = Vec::new();
+        if let Some(im) = info_map {
+            for (k, v) in im {
+                if v.as_string().map_err(|_| anyhow!("Invalid info value"))? == "" {
+                    info.push(k.clone());
+                }
+            }
+        }
+        let pinstall_map = hooks_sec.and_then(|h| h.get("pinstall")).and_then(|v| v.as_map().ok());
+        let mut pinstall = Vec::new();
+        if let Some(pm) = pinstall_map {
+            for (k, v) in pm {
+                if v.as_string().map_err(|_| anyhow!("Invalid pinstall value"))? == "" {
+                    pinstall.push(k.clone());
+                }
+            }
+        }
+        let shells_map = hooks_sec.and_then(|h| h.get("shells")).and_then(|v| v.as_map().ok());
+        let mut shells = Vec::new();
+        if let Some(sm) = shells_map {
+            for (k, v) in sm {
+                if v.as_string().map_err(|_| anyhow!("Invalid shell value"))? == "" {
+                    shells.push(k.clone());
+                }
+            }
+        }
+        let mut users = Vec::new();
+        if let Some(um) = hooks_sec.and_then(|h| h.get("users")).and_then(|v| v.as_map().ok()) {
+            for (name, v) in um {
+                let sub = v.as_map().ok();
+                let uid = sub.and_then(|s| s.get("uid")).and_then(|v| v.as_string().ok());
+                let shell = sub.and_then(|s| s.get("shell")).and_then(|v| v.as_string().ok());
+                users.push(UserHook { name: name.clone(), uid, shell });
+            }
+        }
+        let mut groups = Vec::new();
+        if let Some(gm) = hooks_sec.and_then(|h| h.get("groups")).and_then(|v| v.as_map().ok()) {
+            for (name, v) in gm {
+                let gid = v.as_map().ok().and_then(|s| s.get("gid")).and_then(|v| v.as_string().ok());
+                groups.push(GroupHook { name: name.clone(), gid });
+            }
+        }
+        let hooks = Hooks {
+            man,
+            glib_schema,
+            info,
+            pinstall,
+            users,
+            groups,
+            shells,
+            purge_users,
+        };
         Ok(Manifest {
             name,
             version,
@@ -141,8 +271,11 @@ impl Manifest {
                 filesystem,
                 gui,
                 dev,
+                seccomp,
+                capabilities,
             },
             install_commands,
+            hooks,
         })
     }
 }
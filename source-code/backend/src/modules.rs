@@ -0,0 +1,254 @@
+use crate::manifest::Manifest;
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::process::Command;
+
+/// A single requested change against a [`SoftwareModule`]: either bring a
+/// package to a version, or take it away.
+#[derive(Debug, Clone)]
+pub enum Action {
+    Install,
+    Remove,
+}
+
+impl Action {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Action::Install => "install",
+            Action::Remove => "remove",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Action> {
+        match s {
+            "install" => Ok(Action::Install),
+            "remove" => Ok(Action::Remove),
+            other => Err(anyhow!("Unknown update-list action: {}", other)),
+        }
+    }
+}
+
+/// `(action, package name, version)`. Version is empty for removals.
+pub type Op = (Action, String, String);
+
+/// A backend capable of installing/removing packages of one "type"
+/// (`apt`, `hk`, ...). Modules that can apply a whole batch in a single
+/// transaction should override `update_list`/`supports_update_list`;
+/// everything else falls back to sequential `install`/`remove` calls.
+pub trait SoftwareModule {
+    fn kind(&self) -> &'static str;
+    fn list(&self) -> Result<Vec<(String, String)>>;
+    fn prepare(&self, name: &str, version: &str) -> Result<()>;
+    fn install(&self, name: &str, version: &str) -> Result<()>;
+    fn remove(&self, name: &str, version: &str) -> Result<()>;
+    fn finalize(&self) -> Result<()>;
+
+    fn supports_update_list(&self) -> bool {
+        false
+    }
+
+    fn update_list(&self, ops: &[Op]) -> Result<()> {
+        for (action, name, version) in ops {
+            self.prepare(name, version)?;
+            match action {
+                Action::Install => self.install(name, version)?,
+                Action::Remove => self.remove(name, version)?,
+            }
+        }
+        self.finalize()
+    }
+}
+
+/// Drives `apt-get`/`dpkg` as a single package-backend. Unlike `HkModule`
+/// it can batch a whole `update_list` into one `apt-get install` run by
+/// encoding removals as `pkg-` and pinned installs as `pkg=version`.
+pub struct AptModule;
+
+impl SoftwareModule for AptModule {
+    fn kind(&self) -> &'static str {
+        "apt"
+    }
+
+    fn list(&self) -> Result<Vec<(String, String)>> {
+        let output = Command::new("dpkg-query")
+            .args(["-W", "-f=${Package}\t${Version}\n"])
+            .output()
+            .context("Failed to run dpkg-query")?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(text
+            .lines()
+            .filter_map(|l| l.split_once('\t'))
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect())
+    }
+
+    fn prepare(&self, _name: &str, _version: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn install(&self, name: &str, version: &str) -> Result<()> {
+        let target = if version.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}={}", name, version)
+        };
+        run_apt(&["install", "-y", &target])
+    }
+
+    fn remove(&self, name: &str, _version: &str) -> Result<()> {
+        run_apt(&["remove", "-y", name])
+    }
+
+    fn finalize(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn supports_update_list(&self) -> bool {
+        true
+    }
+
+    fn update_list(&self, ops: &[Op]) -> Result<()> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        let targets: Vec<String> = ops
+            .iter()
+            .map(|(action, name, version)| match action {
+                Action::Install if !version.is_empty() => format!("{}={}", name, version),
+                Action::Install => name.clone(),
+                Action::Remove => format!("{}-", name),
+            })
+            .collect();
+        let mut args = vec!["install".to_string(), "-y".to_string()];
+        args.extend(targets);
+        run_apt(&args.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+}
+
+fn run_apt(args: &[&str]) -> Result<()> {
+    let status = Command::new("sudo")
+        .arg("apt-get")
+        .args(args)
+        .status()
+        .context("Failed to run apt-get")?;
+    if !status.success() {
+        return Err(anyhow!("apt-get {:?} failed with {}", args, status));
+    }
+    Ok(())
+}
+
+/// Drives native `.hk` packages through `Manifest::install_commands`
+/// inside the sandbox; this is the existing install path `install()`
+/// already uses, exposed here so it can take part in mixed transactions.
+pub struct HkModule {
+    pub store_path: String,
+}
+
+impl SoftwareModule for HkModule {
+    fn kind(&self) -> &'static str {
+        "hk"
+    }
+
+    fn list(&self) -> Result<Vec<(String, String)>> {
+        let mut out = Vec::new();
+        for entry in std::fs::read_dir(&self.store_path).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let current = entry.path().join("current");
+            if let Ok(manifest) = Manifest::load_info(current.to_str().unwrap_or_default()) {
+                out.push((name, manifest.version));
+            }
+        }
+        Ok(out)
+    }
+
+    fn prepare(&self, _name: &str, _version: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn install(&self, name: &str, version: &str) -> Result<()> {
+        let path = format!("{}{}/{}", self.store_path, name, version);
+        let manifest = Manifest::load_info(&path)?;
+        crate::sandbox::setup_sandbox(&path, &manifest, true, None, vec![], false)
+    }
+
+    fn remove(&self, name: &str, version: &str) -> Result<()> {
+        let path = format!("{}{}/{}", self.store_path, name, version);
+        std::fs::remove_dir_all(&path).context("Failed to remove hk package tree")
+    }
+
+    fn finalize(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Registry of modules keyed by package "type" (`apt`, `hk`, ...).
+pub struct ModuleRegistry {
+    modules: HashMap<&'static str, Box<dyn SoftwareModule>>,
+}
+
+impl ModuleRegistry {
+    pub fn new(store_path: &str) -> ModuleRegistry {
+        let mut modules: HashMap<&'static str, Box<dyn SoftwareModule>> = HashMap::new();
+        modules.insert("apt", Box::new(AptModule));
+        modules.insert(
+            "hk",
+            Box::new(HkModule {
+                store_path: store_path.to_string(),
+            }),
+        );
+        ModuleRegistry { modules }
+    }
+
+    pub fn get(&self, kind: &str) -> Result<&dyn SoftwareModule> {
+        self.modules
+            .get(kind)
+            .map(|m| m.as_ref())
+            .ok_or_else(|| anyhow!("No registered backend for package type '{}'", kind))
+    }
+
+    /// Apply a batch of `(type, action, name, version)` lines, grouping by
+    /// backend so a module that supports `update_list` gets its whole
+    /// batch in one call instead of per-package.
+    pub fn apply_batch(&self, lines: &[(String, Action, String, String)]) -> Result<()> {
+        let mut grouped: HashMap<&str, Vec<Op>> = HashMap::new();
+        for (kind, action, name, version) in lines {
+            let module = self.get(kind)?;
+            grouped
+                .entry(module.kind())
+                .or_default()
+                .push((action.clone(), name.clone(), version.clone()));
+        }
+        for (kind, ops) in grouped {
+            self.get(kind)?.update_list(&ops)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the `update-list` stdin protocol: one line per op, formatted
+/// `<action>\t<name>\t<version>\t<type>` (version may be empty for
+/// removes). `type` may be omitted entirely, defaulting to `apt`, so the
+/// documented 3-field form (`install\tfoo\t1.2` / `remove\tbar\t`) still
+/// works; a mixed-backend batch spells out the 4th field to route a line
+/// to `hk` (or another registered module) instead.
+pub fn parse_update_list(input: &str) -> Result<Vec<(String, Action, String, String)>> {
+    let mut ops = Vec::new();
+    for line in input.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.splitn(4, '\t').collect();
+        let [action, name] = [
+            fields.first().copied().unwrap_or_default(),
+            fields.get(1).copied().unwrap_or_default(),
+        ];
+        if name.is_empty() {
+            return Err(anyhow!("Malformed update-list line: {}", line));
+        }
+        let version = fields.get(2).copied().unwrap_or_default().to_string();
+        let kind = fields.get(3).copied().unwrap_or("apt").to_string();
+        ops.push((kind, Action::parse(action)?, name.to_string(), version));
+    }
+    Ok(ops)
+}
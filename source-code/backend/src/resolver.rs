@@ -0,0 +1,169 @@
+use crate::manifest::Manifest;
+use indexmap::IndexMap;
+use std::collections::VecDeque;
+
+/// Why dependency resolution failed, kept distinct from a generic
+/// `anyhow::Error` so `main.rs` can map it onto the right `ErrorCode`.
+#[derive(Debug)]
+pub enum ResolveError {
+    /// A cycle remains after Kahn's algorithm drains every zero-in-degree
+    /// node; names the packages still stuck in the graph.
+    Cycle(Vec<String>),
+    /// The same package is required at two versions that don't match.
+    VersionConflict {
+        name: String,
+        wanted: String,
+        also_wanted: String,
+    },
+    /// A dependency's manifest could not be loaded from the store.
+    Missing { name: String, version: String },
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolveError::Cycle(names) => write!(f, "dependency cycle among: {}", names.join(", ")),
+            ResolveError::VersionConflict {
+                name,
+                wanted,
+                also_wanted,
+            } => write!(
+                f,
+                "version conflict for '{}': {} vs {}",
+                name, wanted, also_wanted
+            ),
+            ResolveError::Missing { name, version } => {
+                write!(f, "dependency '{}' {} not found in store", name, version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+struct Node {
+    version: String,
+    deps: IndexMap<String, String>,
+}
+
+/// Recursively load `root`'s dependency graph from the store (each
+/// dependency is expected to already be unpacked at
+/// `<store_path>/<name>/<version>/`), rejecting version conflicts as
+/// they're discovered, then emit an install order via Kahn's
+/// topological sort. The returned order excludes `root` itself; the
+/// caller installs `root` last.
+pub fn resolve(
+    root_name: &str,
+    root_manifest: &Manifest,
+    store_path: &str,
+) -> std::result::Result<Vec<(String, String)>, ResolveError> {
+    let mut graph: IndexMap<String, Node> = IndexMap::new();
+    graph.insert(
+        root_name.to_string(),
+        Node {
+            version: root_manifest.version.clone(),
+            deps: root_manifest.deps.clone(),
+        },
+    );
+
+    let mut queue: VecDeque<String> = root_manifest.deps.keys().cloned().collect();
+    let mut wanted_version: IndexMap<String, String> = root_manifest.deps.clone();
+
+    while let Some(name) = queue.pop_front() {
+        if graph.contains_key(&name) {
+            continue;
+        }
+        let version = wanted_version.get(&name).cloned().unwrap_or_default();
+        let path = format!("{}{}/{}", store_path, name, version);
+        let manifest = Manifest::load_info(&path).map_err(|_| ResolveError::Missing {
+            name: name.clone(),
+            version: version.clone(),
+        })?;
+
+        if manifest.version != version && !version.is_empty() {
+            return Err(ResolveError::VersionConflict {
+                name: name.clone(),
+                wanted: version,
+                also_wanted: manifest.version,
+            });
+        }
+
+        for (dep_name, dep_version) in &manifest.deps {
+            if let Some(existing) = wanted_version.get(dep_name) {
+                if existing != dep_version {
+                    return Err(ResolveError::VersionConflict {
+                        name: dep_name.clone(),
+                        wanted: existing.clone(),
+                        also_wanted: dep_version.clone(),
+                    });
+                }
+            } else {
+                wanted_version.insert(dep_name.clone(), dep_version.clone());
+                queue.push_back(dep_name.clone());
+            }
+        }
+
+        graph.insert(
+            name,
+            Node {
+                version: manifest.version,
+                deps: manifest.deps,
+            },
+        );
+    }
+
+    topo_sort(root_name, &graph)
+}
+
+fn topo_sort(
+    root_name: &str,
+    graph: &IndexMap<String, Node>,
+) -> std::result::Result<Vec<(String, String)>, ResolveError> {
+    // Edge direction: node -> dep means dep must be installed before node,
+    // so a node's in-degree is simply its own unresolved dependency count.
+    let mut in_degree: IndexMap<&str, usize> = IndexMap::new();
+    for (name, node) in graph {
+        in_degree.insert(name.as_str(), node.deps.len());
+    }
+
+    let mut queue: VecDeque<&str> = in_degree
+        .iter()
+        .filter(|(_, &deg)| deg == 0)
+        .map(|(name, _)| *name)
+        .collect();
+
+    let mut order: Vec<(String, String)> = Vec::new();
+    let mut emitted = 0usize;
+
+    while let Some(name) = queue.pop_front() {
+        emitted += 1;
+        let node = &graph[name];
+        order.push((name.to_string(), node.version.clone()));
+
+        for (other_name, other_node) in graph {
+            if other_name == name {
+                continue;
+            }
+            if other_node.deps.contains_key(name) {
+                let deg = in_degree.get_mut(other_name.as_str()).unwrap();
+                *deg -= 1;
+                if *deg == 0 {
+                    queue.push_back(other_name.as_str());
+                }
+            }
+        }
+    }
+
+    if emitted < graph.len() {
+        let remaining: Vec<String> = in_degree
+            .iter()
+            .filter(|(_, &deg)| deg > 0)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        return Err(ResolveError::Cycle(remaining));
+    }
+
+    // root installs last; everything else is a dependency.
+    order.retain(|(name, _)| name != root_name);
+    Ok(order)
+}
@@ -1,5 +1,6 @@
-use crate::manifest::{Manifest, Sandbox};
+use crate::manifest::{Manifest, Sandbox, SeccompPolicy};
 use anyhow::{anyhow, Context as _, Result};
+use caps::{CapSet, Capability};
 use landlock::{
     Access, AccessFs, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreatedAttr, ABI,
 };
@@ -11,6 +12,7 @@ use nix::sys::resource::{setrlimit, Resource};
 use nix::unistd::{chdir, fork, getpid, pipe, pivot_root, read, write, ForkResult, Gid, Uid, sethostname, execve};
 use seccomp::{Action, Compare, Op, Rule};
 use seccomp::Context as SeccompContext;
+use std::collections::HashSet;
 use std::env;
 use std::ffi::{CStr, CString};
 use std::fs::{create_dir_all, File};
@@ -18,10 +20,31 @@ use std::io::Write;
 use std::os::unix::io::{AsRawFd, BorrowedFd};
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::str::FromStr;
 use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 
 pub const STORE_PATH: &str = "/usr/lib/HackerOS/hpm/store/";
 
+/// Why `setup_sandbox` failed, kept distinct from a generic `anyhow::Error`
+/// so `main.rs` can map it onto `ErrorCode::SandboxSetupFailed` instead of
+/// the catch-all `InstallFailed`/`RemoveFailed`.
+#[derive(Debug)]
+pub enum SandboxError {
+    /// The forked child reported a failure over the error pipe before
+    /// `execve`-ing into the package's install commands.
+    ChildFailed(String),
+}
+
+impl std::fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SandboxError::ChildFailed(msg) => write!(f, "sandbox child failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SandboxError {}
+
 pub fn setup_sandbox(
     path: &str,
     manifest: &Manifest,
@@ -40,7 +63,7 @@ pub fn setup_sandbox(
                 let mut buf = vec![0u8; 1024];
                 let n = read(read_fd.as_raw_fd(), &mut buf)?;
                 let msg = String::from_utf8_lossy(&buf[0..n]);
-                return Err(anyhow!("Sandbox child failed: {}", msg));
+                return Err(SandboxError::ChildFailed(msg.into_owned()).into());
             }
             Ok(())
         }
@@ -96,10 +119,11 @@ fn child_setup(
     let display = env::var("DISPLAY").ok();
     setup_mounts(&new_root, path, &manifest.sandbox, display.as_ref())?;
     pivot_and_chdir(&new_root)?;
+    drop_capabilities(manifest).context("Dropping capabilities failed")?;
     prctl::set_no_new_privs().context("Set no new privs failed")?;
     set_resource_limits()?;
     setup_landlock(manifest)?;
-    setup_seccomp()?;
+    setup_seccomp(manifest.sandbox.seccomp)?;
     chdir("/app")?;
     if test { return Ok(()); }
     exec_in_sandbox(is_install, &manifest.install_commands, bin, extra_args)
@@ -211,6 +235,39 @@ fn pivot_and_chdir(new_root: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Clears the ambient, inheritable, effective, permitted, and bounding
+/// capability sets down to empty, re-granting only the names listed under
+/// `[sandbox] capabilities` (e.g. `CAP_NET_BIND_SERVICE` for a `run`
+/// target bound to a privileged port). Must run after mounts/`pivot_root`,
+/// which still need the in-namespace root's privileges, and before
+/// `setup_landlock`'s `restrict_self()`, so nothing downstream can
+/// re-raise what's dropped here. Succeeds unchanged when a set is already
+/// empty, since every step below targets an exact desired set rather than
+/// removing capabilities one at a time.
+fn drop_capabilities(manifest: &Manifest) -> Result<()> {
+    let keep: HashSet<Capability> = manifest
+        .sandbox
+        .capabilities
+        .iter()
+        .filter_map(|name| Capability::from_str(name).ok())
+        .collect();
+
+    // Dropping from the bounding set needs CAP_SETPCAP still effective, and
+    // the effective set must stay within the permitted set, so the bounding
+    // set goes first and the permitted set last.
+    for cap in caps::all() {
+        if !keep.contains(&cap) {
+            caps::drop(None, CapSet::Bounding, cap).context("Failed to drop bounding capability")?;
+        }
+    }
+
+    caps::set(None, CapSet::Inheritable, &keep).context("Failed to set inheritable capabilities")?;
+    caps::set(None, CapSet::Ambient, &keep).context("Failed to set ambient capabilities")?;
+    caps::set(None, CapSet::Effective, &keep).context("Failed to set effective capabilities")?;
+    caps::set(None, CapSet::Permitted, &keep).context("Failed to set permitted capabilities")?;
+    Ok(())
+}
+
 fn set_resource_limits() -> Result<()> {
     setrlimit(Resource::RLIMIT_CPU, 60, 60)?;
     let mem_limit = 512 * 1024 * 1024;
@@ -249,24 +306,117 @@ fn setup_landlock(manifest: &Manifest) -> Result<()> {
     Ok(())
 }
 
-fn setup_seccomp() -> Result<()> {
-    let mut ctx = SeccompContext::default(Action::Errno(libc::EPERM))?;
-    let allowed_syscalls: Vec<i64> = vec![
-        libc::SYS_read, libc::SYS_write, libc::SYS_open, libc::SYS_openat,
-        libc::SYS_close, libc::SYS_exit, libc::SYS_mmap, libc::SYS_brk,
-        libc::SYS_exit_group, libc::SYS_futex, libc::SYS_fstat, libc::SYS_newfstatat,
-    ];
-    for sc in allowed_syscalls {
-        let cmp = Compare::arg(0)
-        .with(0)
-        .and(0)
-        .using(Op::MaskedEq)
-        .build()
-        .ok_or(anyhow!("Failed to build cmp"))?;
-        let rule = Rule::new(sc as usize, cmp, Action::Allow);
-        ctx.add_rule(rule)?;
+/// Known-dangerous syscalls denied in `Relaxed` mode: they can escape the
+/// namespace/landlock confinement (ptrace, process_vm_*, mount family),
+/// touch the kernel keyring or BPF subsystem, or affect the whole machine
+/// (reboot, kexec, swap). `libc::SYS_*` already resolves to the right
+/// number for the target architecture, so this list works unmodified on
+/// both x86_64 and aarch64.
+const DENIED_SYSCALLS: &[i64] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_move_mount,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_kexec_load,
+    libc::SYS_reboot,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+];
+
+/// Syscalls `/bin/sh` and a typical `install_commands` script need, used
+/// as the allowlist in `Strict` mode. Only the `*at`-suffixed path
+/// syscalls are listed here so this const itself compiles on every
+/// architecture; aarch64 and other generic-syscall-ABI targets never got
+/// the legacy non-`at` numbers `LEGACY_ALLOWED_SYSCALLS` adds below.
+const ALLOWED_SYSCALLS: &[i64] = &[
+    libc::SYS_read, libc::SYS_write, libc::SYS_openat,
+    libc::SYS_close, libc::SYS_exit, libc::SYS_mmap, libc::SYS_munmap,
+    libc::SYS_mprotect, libc::SYS_brk, libc::SYS_exit_group, libc::SYS_futex,
+    libc::SYS_fstat, libc::SYS_newfstatat,
+    libc::SYS_execve, libc::SYS_clone,
+    libc::SYS_wait4, libc::SYS_rt_sigaction, libc::SYS_rt_sigprocmask,
+    libc::SYS_rt_sigreturn, libc::SYS_dup, libc::SYS_pipe2, libc::SYS_ioctl, libc::SYS_fcntl, libc::SYS_getdents64,
+    libc::SYS_mkdirat, libc::SYS_unlinkat,
+    libc::SYS_renameat, libc::SYS_fchmodat, libc::SYS_fchownat,
+    libc::SYS_readlinkat, libc::SYS_getcwd, libc::SYS_chdir, libc::SYS_getpid,
+    libc::SYS_getppid, libc::SYS_getuid, libc::SYS_geteuid, libc::SYS_getgid,
+    libc::SYS_getegid, libc::SYS_set_tid_address,
+    libc::SYS_set_robust_list, libc::SYS_prlimit64, libc::SYS_sched_getaffinity,
+    libc::SYS_statx, libc::SYS_getrandom, libc::SYS_clone3, libc::SYS_rseq,
+];
+
+/// x86_64-only counterparts of the syscalls above: `open`/`stat`/`lstat`/
+/// `access` next to `openat`/`newfstatat`, `fork`/`vfork` next to `clone`,
+/// `dup2`/`pipe` next to `dup`/`pipe2`, `mkdir`/`unlink`/`rmdir`/`rename`/
+/// `chmod`/`chown`/`readlink` next to their `*at` forms, and `arch_prctl`
+/// (x86-specific FS/GS-base setup; aarch64 has no equivalent syscall
+/// number at all). `libc` doesn't define these constants off x86_64, so
+/// the whole const is gated rather than just its call site.
+#[cfg(target_arch = "x86_64")]
+const LEGACY_ALLOWED_SYSCALLS: &[i64] = &[
+    libc::SYS_open,
+    libc::SYS_stat,
+    libc::SYS_lstat,
+    libc::SYS_access,
+    libc::SYS_fork,
+    libc::SYS_vfork,
+    libc::SYS_dup2,
+    libc::SYS_pipe,
+    libc::SYS_mkdir,
+    libc::SYS_unlink,
+    libc::SYS_rmdir,
+    libc::SYS_rename,
+    libc::SYS_chmod,
+    libc::SYS_chown,
+    libc::SYS_readlink,
+    libc::SYS_arch_prctl,
+];
+
+/// Unconditional match: `arg(0) & 0 == 0` is true for every call, so the
+/// rule fires regardless of the syscall's actual arguments.
+fn always() -> Result<Compare> {
+    Compare::arg(0)
+    .with(0)
+    .and(0)
+    .using(Op::MaskedEq)
+    .build()
+    .ok_or(anyhow!("Failed to build cmp"))
+}
+
+/// Installs the seccomp-bpf filter right before `execve`, after
+/// `restrict_self()` and `prctl::set_no_new_privs()` have already run.
+/// Denied/unlisted syscalls return `EPERM` instead of killing the process,
+/// so an install script that hits one fails with a normal error rather
+/// than being signal-killed.
+fn setup_seccomp(policy: SeccompPolicy) -> Result<()> {
+    match policy {
+        SeccompPolicy::Strict => {
+            let mut ctx = SeccompContext::default(Action::Errno(libc::EPERM))?;
+            for sc in ALLOWED_SYSCALLS {
+                ctx.add_rule(Rule::new(*sc as usize, always()?, Action::Allow))?;
+            }
+            #[cfg(target_arch = "x86_64")]
+            for sc in LEGACY_ALLOWED_SYSCALLS {
+                ctx.add_rule(Rule::new(*sc as usize, always()?, Action::Allow))?;
+            }
+            ctx.load()?;
+        }
+        SeccompPolicy::Relaxed => {
+            let mut ctx = SeccompContext::default(Action::Allow)?;
+            for sc in DENIED_SYSCALLS {
+                ctx.add_rule(Rule::new(*sc as usize, always()?, Action::Errno(libc::EPERM)))?;
+            }
+            ctx.load()?;
+        }
     }
-    ctx.load()?;
     Ok(())
 }
 
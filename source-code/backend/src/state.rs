@@ -6,9 +6,19 @@ use std::path::Path;
 
 const STATE_PATH: &str = "/var/lib/hpm/state.json";
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct State {
     pub packages: HashMap<String, HashMap<String, String>>,
+    /// How many installed packages currently hard-link each content-store
+    /// object, keyed by its hex hash. An object reaches zero only when the
+    /// last package referencing it is removed, at which point its blob can
+    /// be unlinked from `STORE_PATH/objects/`.
+    #[serde(default)]
+    pub object_refcounts: HashMap<String, u64>,
+    /// Which object hashes `"<name>/<version>"` hard-links into, so
+    /// `release_objects` knows what to decrement on removal.
+    #[serde(default)]
+    pub package_objects: HashMap<String, Vec<String>>,
 }
 
 pub fn load_state() -> Result<State> {
@@ -27,12 +37,47 @@ pub fn save_state(state: &State) -> Result<()> {
     Ok(())
 }
 
-pub fn update_state(package_name: &str, version: &str, checksum: &str) -> Result<()> {
+pub fn update_state(package_name: &str, version: &str, checksum: &str, object_hashes: &[String]) -> Result<()> {
     let mut state = load_state()?;
     state
     .packages
     .entry(package_name.to_string())
     .or_insert_with(HashMap::new)
     .insert(version.to_string(), checksum.to_string());
+    record_objects(&mut state, package_name, version, object_hashes);
     save_state(&state)
 }
+
+/// Adds a refcount for every hash in `object_hashes` and remembers the
+/// list under `"<package_name>/<version>"` so a later [`release_objects`]
+/// for the same package/version knows what to decrement. Idempotent per
+/// `package_name`/`version`: re-recording the same key first releases
+/// whatever it previously held, so reinstalling an already-recorded
+/// version doesn't double-increment objects it already references.
+pub fn record_objects(state: &mut State, package_name: &str, version: &str, object_hashes: &[String]) {
+    release_objects(state, package_name, version);
+    for hash in object_hashes {
+        *state.object_refcounts.entry(hash.clone()).or_insert(0) += 1;
+    }
+    let key = format!("{}/{}", package_name, version);
+    state.package_objects.insert(key, object_hashes.to_vec());
+}
+
+/// Decrements the refcount of every object `"<package_name>/<version>"`
+/// referenced, dropping entries that hit zero. Returns the hashes that hit
+/// zero, i.e. the blobs [`crate::store::unlink_objects`] can now remove.
+pub fn release_objects(state: &mut State, package_name: &str, version: &str) -> Vec<String> {
+    let key = format!("{}/{}", package_name, version);
+    let hashes = state.package_objects.remove(&key).unwrap_or_default();
+    let mut freed = Vec::new();
+    for hash in &hashes {
+        if let Some(count) = state.object_refcounts.get_mut(hash) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                state.object_refcounts.remove(hash);
+                freed.push(hash.clone());
+            }
+        }
+    }
+    freed
+}
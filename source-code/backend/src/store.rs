@@ -0,0 +1,112 @@
+//! Content-addressed blob pool under `STORE_PATH/objects/<hash[0:2]>/<hash>`.
+//! Installed package trees are reconstructed as hard links into this pool
+//! so identical files (shared libraries, assets, duplicate versions)
+//! occupy disk once no matter how many packages reference them.
+use crate::verify::FileEntry;
+use crate::STORE_PATH;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+fn objects_dir() -> PathBuf {
+    Path::new(STORE_PATH).join("objects")
+}
+
+fn object_path(hash_hex: &str) -> PathBuf {
+    objects_dir().join(&hash_hex[0..2]).join(hash_hex)
+}
+
+/// Moves every regular file out of `staged` into the object pool
+/// (deduplicating against a blob already there), reconstructs `dest` as a
+/// tree of hard links into the pool plus freshly-recreated symlinks, then
+/// removes the now-empty `staged` tree. Returns the object hash of every
+/// regular file `dest` now depends on.
+pub fn materialize(staged: &Path, dest: &Path, manifest: &[FileEntry]) -> Result<Vec<String>> {
+    fs::create_dir_all(dest).context("Failed to create destination directory")?;
+    let hashes = link_into_pool(staged, dest, manifest, false)?;
+    fs::remove_dir_all(staged).context("Failed to remove staged tree")?;
+    Ok(hashes)
+}
+
+/// Deduplicates a tree that's already sitting at its final install path
+/// (the `install_local`/dependency path, which has no separate staging
+/// step): every regular file is moved into the object pool and replaced
+/// with a hard link back to the same path, in place.
+pub fn materialize_in_place(tree: &Path, manifest: &[FileEntry]) -> Result<Vec<String>> {
+    link_into_pool(tree, tree, manifest, true)
+}
+
+fn link_into_pool(src_root: &Path, dest_root: &Path, manifest: &[FileEntry], in_place: bool) -> Result<Vec<String>> {
+    let mut hashes = Vec::with_capacity(manifest.len());
+    for entry in manifest {
+        let src = src_root.join(&entry.relative_path);
+        let dst = dest_root.join(&entry.relative_path);
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent).context("Failed to create destination directory")?;
+        }
+        if entry.is_symlink {
+            if !in_place {
+                let target = fs::read_link(&src)
+                    .with_context(|| format!("Failed to read staged symlink {}", entry.relative_path))?;
+                symlink(&target, &dst)
+                    .with_context(|| format!("Failed to recreate symlink {}", entry.relative_path))?;
+            }
+            continue;
+        }
+        let hash_hex = hex::encode(entry.hash);
+        let obj = object_path(&hash_hex);
+        if !obj.exists() {
+            if let Some(parent) = obj.parent() {
+                fs::create_dir_all(parent).context("Failed to create object shard directory")?;
+            }
+            fs::rename(&src, &obj)
+                .with_context(|| format!("Failed to move {} into object store", entry.relative_path))?;
+        } else {
+            fs::remove_file(&src).with_context(|| format!("Failed to discard duplicate of {}", entry.relative_path))?;
+        }
+        fs::hard_link(&obj, &dst)
+            .with_context(|| format!("Failed to hard-link {} from object store", entry.relative_path))?;
+        hashes.push(hash_hex);
+    }
+    Ok(hashes)
+}
+
+/// Unlinks each blob in `hashes` from the object pool. Callers are
+/// expected to only pass hashes [`crate::state::release_objects`] has
+/// already confirmed have no remaining references.
+pub fn unlink_objects(hashes: &[String]) -> Result<()> {
+    for hash in hashes {
+        let _ = fs::remove_file(object_path(hash));
+    }
+    Ok(())
+}
+
+/// Sweeps `objects/` for blobs missing from `refcounts` (either a
+/// zero-refcount object whose removal was interrupted, or a blob orphaned
+/// by a crash between the rename into the pool and the state save that
+/// should have recorded it) and removes them. Returns how many were swept.
+pub fn gc(refcounts: &HashMap<String, u64>) -> Result<usize> {
+    let dir = objects_dir();
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for shard in fs::read_dir(&dir).context("Failed to read objects directory")? {
+        let shard = shard?;
+        if !shard.file_type()?.is_dir() {
+            continue;
+        }
+        for obj in fs::read_dir(shard.path())? {
+            let obj = obj?;
+            let hash = obj.file_name().to_string_lossy().into_owned();
+            let referenced = refcounts.get(&hash).map(|count| *count > 0).unwrap_or(false);
+            if !referenced {
+                fs::remove_file(obj.path())?;
+                removed += 1;
+            }
+        }
+    }
+    Ok(removed)
+}
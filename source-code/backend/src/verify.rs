@@ -1,30 +1,113 @@
-use anyhow::{anyhow, Result};
+use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
 use walkdir::WalkDir;
 
+/// One regular file or symlink in a package tree, keyed by its path
+/// relative to the tree root so the manifest doesn't depend on where the
+/// tree happens to be mounted.
+pub struct FileEntry {
+    pub relative_path: String,
+    /// Raw SHA-256 digest of the file's contents, or of its target string
+    /// when `relative_path` names a symlink — symlinks are never followed,
+    /// so a malicious link can't pull the hash from outside the tree.
+    pub hash: [u8; 32],
+    pub is_symlink: bool,
+}
+
+/// Walks `dir` and returns one `FileEntry` per regular file or symlink,
+/// sorted by relative path bytes so the manifest (and the root digest
+/// built from it) is deterministic regardless of directory iteration
+/// order.
+pub fn build_manifest(dir: &Path) -> Result<Vec<FileEntry>> {
+    let mut entries = Vec::new();
+    for entry in WalkDir::new(dir).follow_links(false).into_iter() {
+        let entry = entry.context("Failed to walk package tree")?;
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            continue;
+        }
+        let relative_path = entry
+            .path()
+            .strip_prefix(dir)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .into_owned();
+        let is_symlink = file_type.is_symlink();
+        let hash = if is_symlink {
+            let target =
+                fs::read_link(entry.path()).with_context(|| format!("Failed to read symlink target for {}", relative_path))?;
+            hash_bytes(target.to_string_lossy().as_bytes())
+        } else {
+            let data = fs::read(entry.path()).with_context(|| format!("Failed to read {}", relative_path))?;
+            hash_bytes(&data)
+        };
+        entries.push(FileEntry { relative_path, hash, is_symlink });
+    }
+    entries.sort_by(|a, b| a.relative_path.as_bytes().cmp(b.relative_path.as_bytes()));
+    Ok(entries)
+}
+
+fn hash_bytes(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Canonically serializes a sorted file manifest (path length + path
+/// bytes + file hash, concatenated in order) and hashes that serialization
+/// to produce a single root digest covering the whole tree, not just one
+/// file's contents.
+pub fn root_digest(entries: &[FileEntry]) -> String {
+    let mut hasher = Sha256::new();
+    for entry in entries {
+        hasher.update((entry.relative_path.len() as u64).to_le_bytes());
+        hasher.update(entry.relative_path.as_bytes());
+        hasher.update(entry.hash);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Recomputes the Merkle root over every file (and symlink target) under
+/// `path` and compares it to `checksum`. Any tampering with a binary,
+/// library, or install script anywhere in the tree changes the root, not
+/// just edits to a single metadata file.
 pub fn verify(path: &str, checksum: &str) -> Result<()> {
-    let computed = compute_dir_hash(Path::new(path))?;
+    verify_tree(path, checksum).map(|_| ())
+}
+
+/// Same as [`verify`], but also returns the manifest it built, so callers
+/// like [`crate::store::materialize`] that need the per-file hashes don't
+/// have to walk the tree a second time.
+pub fn verify_tree(path: &str, checksum: &str) -> Result<Vec<FileEntry>> {
+    let manifest = build_manifest(Path::new(path))?;
+    let computed = root_digest(&manifest);
     if computed != checksum {
-        return Err(anyhow!("Checksum mismatch: computed {}, expected {}", computed, checksum));
+        return Err(mismatch_error(&manifest, &computed, checksum));
     }
-    Ok(())
+    Ok(manifest)
 }
 
+/// Builds a diagnostic error naming every file that went into the failed
+/// root digest, since the single root hash alone doesn't say which path
+/// changed — the caller can compare this list against the package's
+/// published manifest to find the culprit.
+fn mismatch_error(manifest: &[FileEntry], computed: &str, expected: &str) -> anyhow::Error {
+    let paths: Vec<&str> = manifest.iter().map(|e| e.relative_path.as_str()).collect();
+    anyhow::anyhow!(
+        "Checksum mismatch: computed {} over {} file(s) ({}), expected {}",
+        computed,
+        manifest.len(),
+        paths.join(", "),
+        expected
+    )
+}
+
+/// Builds the manifest of `dir` and returns its root digest the same way
+/// [`verify`] does, for a local tree without a pre-existing checksum to
+/// compare against.
 pub fn compute_dir_hash(dir: &Path) -> Result<String> {
-    let entries: Vec<_> = WalkDir::new(dir)
-    .sort_by(|a, b| a.file_name().cmp(b.file_name()))
-    .into_iter()
-    .filter_map(|e| e.ok())
-    .filter(|e| e.file_type().is_file())
-    .map(|e| e.path().to_owned())
-    .collect();
-    let mut hasher = Sha256::new();
-    for file_path in entries {
-        let data = fs::read(&file_path)?;
-        hasher.update(&data);
-    }
-    let hash = hasher.finalize();
-    Ok(hex::encode(hash))
+    let manifest = build_manifest(dir)?;
+    Ok(root_digest(&manifest))
 }
@@ -1,4 +1,9 @@
 use anyhow::{Context, Result};
+use futures::future::join_all;
+use std::future::Future;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::time::Duration;
 use tokio::process::Command as AsyncCommand;
 
 #[derive(Clone)]
@@ -6,6 +11,9 @@ pub struct Package {
     pub name: String,
     pub source: Source,
     pub description: String,
+    /// Empty when the backend's search output doesn't surface a version
+    /// (APT's `apt-cache search` doesn't; Snap and Flatpak do).
+    pub version: String,
 }
 
 #[derive(Clone, PartialEq)]
@@ -27,6 +35,37 @@ impl Source {
     }
 }
 
+/// A pending update for one package, found by scanning a backend's own
+/// "what's upgradable" listing rather than re-running a full search.
+#[derive(Clone)]
+pub struct Upgrade {
+    pub name: String,
+    pub source: Source,
+    /// Empty when the backend's listing doesn't surface the installed
+    /// version (Snap and Flatpak don't; APT does).
+    pub current: String,
+    pub candidate: String,
+}
+
+impl Upgrade {
+    /// Builds the `Package` an `upgrade_batch` call acts on.
+    fn to_package(&self) -> Package {
+        Package {
+            name: self.name.clone(),
+            source: self.source.clone(),
+            description: String::new(),
+            version: self.candidate.clone(),
+        }
+    }
+}
+
+/// Which list `ui()` renders and `i`/`r` act on.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    Browse,
+    Upgradable,
+}
+
 pub enum InputMode {
     Normal,
     Editing,
@@ -40,6 +79,31 @@ pub struct App {
     pub selected_source: Source,
     pub message: String,
     pub dot_count: usize,
+    pub busy: bool,
+    /// Set when `--sudoloop` was passed; gates whether the first
+    /// install/remove of the session primes a [`SudoLoopHandle`] at all.
+    pub sudoloop_enabled: bool,
+    /// The credential-refresh task currently running, if any. Started by
+    /// `spawn_op`/`spawn_upgrade` right before a privileged batch and
+    /// stopped once that batch's `OpDone` lands, so the UI can show
+    /// "elevated mode active" for exactly as long as it's true.
+    pub sudoloop: Option<SudoLoopHandle>,
+    /// `(name, source)` pairs recorded as installed in the local cache,
+    /// kept in memory so `ui()` can mark them without a query per frame.
+    pub installed: std::collections::HashSet<(String, &'static str)>,
+    /// Indices into `get_filtered_packages()` (or, in `Mode::Upgradable`,
+    /// into `upgrades`) toggled with Space, acted on together by `i`/`r`
+    /// instead of just the highlighted row.
+    pub selected: std::collections::HashSet<usize>,
+    pub mode: Mode,
+    pub upgrades: Vec<Upgrade>,
+    /// Set while the details popup opened with `o` is on screen; `Esc`
+    /// clears it back to `None`.
+    pub details: Option<crate::details::PackageDetails>,
+    /// Set when `packages` is currently showing `search_cache`'s rows
+    /// rather than a resolved live search, so `ui()` can mark the list
+    /// "(cached)" until the live `SearchResult` replaces them.
+    pub showing_cached: bool,
 }
 
 impl App {
@@ -52,9 +116,22 @@ impl App {
             selected_source: Source::All,
             message: String::new(),
             dot_count: 0,
+            busy: false,
+            sudoloop_enabled: false,
+            sudoloop: None,
+            installed: std::collections::HashSet::new(),
+            selected: std::collections::HashSet::new(),
+            mode: Mode::Browse,
+            upgrades: Vec::new(),
+            details: None,
+            showing_cached: false,
         }
     }
 
+    pub fn is_installed(&self, pkg: &Package) -> bool {
+        self.installed.contains(&(pkg.name.clone(), pkg.source.as_str()))
+    }
+
     pub fn get_filtered_packages(&self) -> Vec<Package> {
         if self.selected_source == Source::All {
             self.packages.clone()
@@ -66,126 +143,641 @@ impl App {
                 .collect()
         }
     }
+
+    pub fn selected_package(&self) -> Option<Package> {
+        let index = self.package_list_state.selected()?;
+        self.get_filtered_packages().get(index).cloned()
+    }
+
+    /// Toggles the highlighted row's checkmark in `selected`.
+    pub fn toggle_selected(&mut self) {
+        if let Some(index) = self.package_list_state.selected() {
+            if !self.selected.remove(&index) {
+                self.selected.insert(index);
+            }
+        }
+    }
+
+    /// The set `i`/`r` should act on: every checked row, or (when nothing
+    /// is checked) just the highlighted one, so single-row use stays as
+    /// it was before multi-select existed.
+    pub fn selected_packages(&self) -> Vec<Package> {
+        if self.selected.is_empty() {
+            return self.selected_package().into_iter().collect();
+        }
+        let filtered = self.get_filtered_packages();
+        self.selected.iter().filter_map(|i| filtered.get(*i).cloned()).collect()
+    }
+
+    /// The `Mode::Upgradable` equivalent of `selected_packages`: every
+    /// checked row in `upgrades`, or just the highlighted one.
+    pub fn selected_upgrades(&self) -> Vec<Package> {
+        if self.selected.is_empty() {
+            let index = self.package_list_state.selected();
+            return index
+                .and_then(|i| self.upgrades.get(i))
+                .map(Upgrade::to_package)
+                .into_iter()
+                .collect();
+        }
+        self.upgrades
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.selected.contains(i))
+            .map(|(_, u)| u.to_package())
+            .collect()
+    }
+
+    /// Checks every row in `upgrades`, so the next `i` upgrades the whole
+    /// list instead of just the highlighted row.
+    pub fn select_all_upgrades(&mut self) {
+        self.selected = (0..self.upgrades.len()).collect();
+    }
+
+    /// Caches every package from a fresh live search; best-effort, since a
+    /// missing or unwritable cache database shouldn't block showing results.
+    pub fn cache_results(&self, packages: &[Package]) {
+        if let Ok(conn) = crate::database::open_db() {
+            let _ = crate::database::cache_packages(&conn, packages);
+        }
+    }
+
+    /// Rows already cached matching `query`, for an instant first-pass
+    /// result while the live search resolves. Empty if the cache can't be
+    /// opened or has nothing matching yet.
+    pub fn search_cache(&self, query: &str) -> Vec<Package> {
+        crate::database::open_db()
+            .and_then(|conn| crate::database::search_cached(&conn, query))
+            .unwrap_or_default()
+    }
+}
+
+/// Why a package-backend shell-out failed, kept distinct from a generic
+/// `anyhow::Error` (via `downcast_ref`) so `main` can exit with a more
+/// specific code than a blanket `1`, and the TUI could style a failure
+/// differently depending on which of these produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppExitCode {
+    /// The backend binary itself (`apt-cache`, `snap`, `flatpak`, ...)
+    /// isn't on `PATH`, as opposed to it running and finding no results.
+    BackendMissing(String),
+    InstallFailed(String),
+    RemoveFailed(String),
+    SearchFailed(String),
+}
+
+impl AppExitCode {
+    fn message(&self) -> &str {
+        match self {
+            AppExitCode::BackendMissing(msg)
+            | AppExitCode::InstallFailed(msg)
+            | AppExitCode::RemoveFailed(msg)
+            | AppExitCode::SearchFailed(msg) => msg,
+        }
+    }
+}
+
+impl std::fmt::Display for AppExitCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
 }
 
+impl std::error::Error for AppExitCode {}
+
+impl From<&AppExitCode> for i32 {
+    fn from(code: &AppExitCode) -> i32 {
+        match code {
+            AppExitCode::BackendMissing(_) => 10,
+            AppExitCode::InstallFailed(_) => 11,
+            AppExitCode::RemoveFailed(_) => 12,
+            AppExitCode::SearchFailed(_) => 13,
+        }
+    }
+}
+
+/// Builder around `tokio::process::Command` that every package-manager
+/// shell-out in this module goes through, so the `sudo`-prefixing,
+/// auto-yes flag, and UTF-8/stderr handling live in one place instead of
+/// being hand-rolled slightly differently in `search_*`/`install_package`/
+/// `remove_package`.
+struct ShellCommand {
+    program: String,
+    args: Vec<String>,
+    elevated: bool,
+    non_interactive: bool,
+    noconfirm: bool,
+}
+
+impl ShellCommand {
+    fn new(program: &str) -> ShellCommand {
+        ShellCommand {
+            program: program.to_string(),
+            args: Vec::new(),
+            elevated: false,
+            non_interactive: false,
+            noconfirm: false,
+        }
+    }
+
+    fn args<I, S>(mut self, args: I) -> ShellCommand
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args.extend(args.into_iter().map(|s| s.as_ref().to_string()));
+        self
+    }
+
+    /// Prepends `sudo` unless we're already running as root, passing `-n`
+    /// when `non_interactive` so a cached-credential miss fails fast
+    /// instead of blocking on a prompt the TUI has no way to show.
+    fn elevated(mut self, non_interactive: bool) -> ShellCommand {
+        self.elevated = true;
+        self.non_interactive = non_interactive;
+        self
+    }
+
+    /// Injects the backend-appropriate auto-yes flag (`-y` for apt,
+    /// `--assumeyes` for flatpak; snap's `install`/`remove`/`refresh`
+    /// don't prompt, so there's nothing to inject).
+    fn noconfirm(mut self, yes: bool) -> ShellCommand {
+        self.noconfirm = yes;
+        self
+    }
+
+    fn build(&self) -> AsyncCommand {
+        let mut args = self.args.clone();
+        if self.noconfirm {
+            match self.program.as_str() {
+                "apt" => args.push("-y".to_string()),
+                "flatpak" => args.push("--assumeyes".to_string()),
+                _ => {}
+            }
+        }
+
+        let already_root = unsafe { libc::geteuid() == 0 };
+        if self.elevated && !already_root {
+            let mut cmd = AsyncCommand::new("sudo");
+            if self.non_interactive {
+                cmd.arg("-n");
+            }
+            cmd.arg(&self.program).args(&args);
+            cmd
+        } else {
+            let mut cmd = AsyncCommand::new(&self.program);
+            cmd.args(&args);
+            cmd
+        }
+    }
+
+    /// Runs the command, returning stdout on success and an `anyhow!`
+    /// built from the command's own stderr on a non-zero exit.
+    async fn wait_success(&self) -> Result<String> {
+        let output = self.build().output().await.with_context(|| format!("Failed to execute {}", self.program))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(anyhow::anyhow!(String::from_utf8_lossy(&output.stderr).into_owned()))
+        }
+    }
+
+    /// Like `wait_success`, but for read-only listing commands: splits
+    /// stdout into lines on success, and still surfaces a non-zero exit
+    /// as an `anyhow!` of stderr rather than silently returning nothing.
+    async fn output_lines(&self) -> Result<Vec<String>> {
+        let output = self.build().output().await.with_context(|| format!("Failed to execute {}", self.program))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect())
+        } else {
+            Err(anyhow::anyhow!(String::from_utf8_lossy(&output.stderr).into_owned()))
+        }
+    }
+}
+
+/// Runs the three backend searches concurrently via `join_all` rather
+/// than one after another, so a slow `snap find` doesn't hold up APT and
+/// Flatpak results.
 pub async fn search_packages(input: String) -> Result<Vec<Package>> {
+    let searches: Vec<Pin<Box<dyn Future<Output = Result<Vec<Package>>> + Send>>> = vec![
+        Box::pin(search_apt(input.clone())),
+        Box::pin(search_snap(input.clone())),
+        Box::pin(search_flatpak(input.clone())),
+    ];
     let mut packages = Vec::new();
-    // Search APT
-    let apt_output = AsyncCommand::new("apt-cache")
-        .arg("search")
-        .arg("--names-only")
-        .arg(&input)
-        .output()
-        .await
-        .context("Failed to execute apt-cache search")?;
-    if apt_output.status.success() {
-        let apt_str = String::from_utf8_lossy(&apt_output.stdout);
-        for line in apt_str.lines() {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                if let Some((name, desc)) = trimmed.split_once(" - ") {
-                    packages.push(Package {
-                        name: name.trim().to_string(),
-                        source: Source::Apt,
-                        description: desc.trim().to_string(),
-                    });
-                }
+    for result in join_all(searches).await {
+        match result {
+            Ok(found) => packages.extend(found),
+            Err(err) => return Err(AppExitCode::SearchFailed(err.to_string()).into()),
+        }
+    }
+    Ok(packages)
+}
+
+/// Distinguishes "the backend binary isn't installed" from "it ran and
+/// exited non-zero", since only the former is worth failing the whole
+/// search over; the latter is just as likely to mean "no matches".
+fn is_backend_missing(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<std::io::Error>()
+        .map(|e| e.kind() == std::io::ErrorKind::NotFound)
+        .unwrap_or(false)
+}
+
+/// A single backend failing (network hiccup, non-zero exit) shouldn't sink
+/// the other two, so those failures fall back to "no results" here; only a
+/// missing binary propagates, as an [`AppExitCode::BackendMissing`].
+async fn search_apt(input: String) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    let lines = match ShellCommand::new("apt-cache").args(["search", "--names-only", input.as_str()]).output_lines().await {
+        Ok(lines) => lines,
+        Err(err) if is_backend_missing(&err) => {
+            return Err(AppExitCode::BackendMissing("apt-cache not found".to_string()).into());
+        }
+        Err(_) => Vec::new(),
+    };
+    for line in lines {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            if let Some((name, desc)) = trimmed.split_once(" - ") {
+                packages.push(Package {
+                    name: name.trim().to_string(),
+                    source: Source::Apt,
+                    description: desc.trim().to_string(),
+                    version: String::new(),
+                });
             }
         }
     }
-    // Search Snap
-    let snap_output = AsyncCommand::new("snap")
-        .arg("find")
-        .arg(&input)
+    Ok(packages)
+}
+
+async fn search_snap(input: String) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    let lines = match ShellCommand::new("snap").args(["find", input.as_str()]).output_lines().await {
+        Ok(lines) => lines,
+        Err(err) if is_backend_missing(&err) => {
+            return Err(AppExitCode::BackendMissing("snap not found".to_string()).into());
+        }
+        Err(_) => Vec::new(),
+    };
+    let start = if !lines.is_empty() && lines[0].contains("Name") { 1 } else { 0 };
+    for line in &lines[start..] {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            let parts: Vec<&str> = trimmed.split_whitespace().collect();
+            if parts.len() >= 5 {
+                let name = parts[0].to_string();
+                let version = parts[1].to_string();
+                let description = parts[4..].join(" ");
+                packages.push(Package {
+                    name,
+                    source: Source::Snap,
+                    description,
+                    version,
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+async fn search_flatpak(input: String) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    let lines = match ShellCommand::new("flatpak").args(["search", input.as_str()]).output_lines().await {
+        Ok(lines) => lines,
+        Err(err) if is_backend_missing(&err) => {
+            return Err(AppExitCode::BackendMissing("flatpak not found".to_string()).into());
+        }
+        Err(_) => Vec::new(),
+    };
+    let start = if !lines.is_empty() && lines[0].contains("Name") { 1 } else { 0 };
+    for line in &lines[start..] {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            let parts: Vec<&str> = trimmed.split('\t').collect();
+            if parts.len() >= 3 {
+                let name = parts[2].to_string(); // Application ID
+                let description = format!("{} - {}", parts.first().unwrap_or(&""), parts.get(1).unwrap_or(&""));
+                let version = parts.get(3).unwrap_or(&"").to_string();
+                packages.push(Package {
+                    name,
+                    source: Source::Flatpak,
+                    description,
+                    version,
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// Runs the three backends' own "what's upgradable" listings concurrently,
+/// same `join_all` pattern as `search_packages`.
+pub async fn list_upgradable() -> Result<Vec<Upgrade>> {
+    let listings: Vec<Pin<Box<dyn Future<Output = Result<Vec<Upgrade>>> + Send>>> = vec![
+        Box::pin(list_apt_upgrades()),
+        Box::pin(list_snap_upgrades()),
+        Box::pin(list_flatpak_upgrades()),
+    ];
+    let mut upgrades = Vec::new();
+    for result in join_all(listings).await {
+        upgrades.extend(result?);
+    }
+    Ok(upgrades)
+}
+
+/// Parses `apt list --upgradable` lines of the form
+/// `name/suite version arch [upgradable from: old-version]`, keeping only
+/// entries where `crate::version` genuinely orders the candidate above
+/// the installed version.
+async fn list_apt_upgrades() -> Result<Vec<Upgrade>> {
+    let mut upgrades = Vec::new();
+    let output = AsyncCommand::new("apt")
+        .arg("list")
+        .arg("--upgradable")
         .output()
         .await
-        .context("Failed to execute snap find")?;
-    if snap_output.status.success() {
-        let snap_str = String::from_utf8_lossy(&snap_output.stdout);
-        let lines: Vec<&str> = snap_str.lines().collect();
-        let start = if !lines.is_empty() && lines[0].contains("Name") { 1 } else { 0 };
-        for line in &lines[start..] {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                let parts: Vec<&str> = trimmed.split_whitespace().collect();
-                if parts.len() >= 5 {
-                    let name = parts[0].to_string();
-                    let description = parts[4..].join(" ");
-                    packages.push(Package {
-                        name,
-                        source: Source::Snap,
-                        description,
-                    });
-                }
+        .context("Failed to execute apt list --upgradable")?;
+    if output.status.success() {
+        let text = String::from_utf8_lossy(&output.stdout);
+        for line in text.lines() {
+            if !line.contains("upgradable from:") {
+                continue;
+            }
+            let Some((head, rest)) = line.split_once(' ') else { continue };
+            let Some(name) = head.split('/').next() else { continue };
+            let mut fields = rest.split_whitespace();
+            let Some(candidate) = fields.next() else { continue };
+            let Some(current) = line.rsplit_once("upgradable from: ").map(|(_, v)| v.trim_end_matches(']')) else {
+                continue;
+            };
+            if crate::version::is_newer(current, candidate) {
+                upgrades.push(Upgrade {
+                    name: name.to_string(),
+                    source: Source::Apt,
+                    current: current.to_string(),
+                    candidate: candidate.to_string(),
+                });
             }
         }
     }
-    // Search Flatpak
-    let flatpak_output = AsyncCommand::new("flatpak")
-        .arg("search")
-        .arg(&input)
+    Ok(upgrades)
+}
+
+/// Parses `snap refresh --list`, whose columns are
+/// `Name Version Rev Size Publisher Notes` and which lists only snaps
+/// with a pending refresh; the first two columns give name and candidate.
+async fn list_snap_upgrades() -> Result<Vec<Upgrade>> {
+    let mut upgrades = Vec::new();
+    let output = AsyncCommand::new("snap")
+        .arg("refresh")
+        .arg("--list")
         .output()
         .await
-        .context("Failed to execute flatpak search")?;
-    if flatpak_output.status.success() {
-        let flatpak_str = String::from_utf8_lossy(&flatpak_output.stdout);
-        let lines: Vec<&str> = flatpak_str.lines().collect();
+        .context("Failed to execute snap refresh --list")?;
+    if output.status.success() {
+        let text = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = text.lines().collect();
         let start = if !lines.is_empty() && lines[0].contains("Name") { 1 } else { 0 };
         for line in &lines[start..] {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                let parts: Vec<&str> = trimmed.split('\t').collect();
-                if parts.len() >= 3 {
-                    let name = parts[2].to_string(); // Application ID
-                    let description = format!("{} - {}", parts.get(0).unwrap_or(&""), parts.get(1).unwrap_or(&""));
-                    packages.push(Package {
-                        name,
-                        source: Source::Flatpak,
-                        description,
-                    });
-                }
+            let parts: Vec<&str> = line.trim().split_whitespace().collect();
+            if parts.len() >= 2 {
+                upgrades.push(Upgrade {
+                    name: parts[0].to_string(),
+                    source: Source::Snap,
+                    current: String::new(),
+                    candidate: parts[1].to_string(),
+                });
             }
         }
     }
-    Ok(packages)
+    Ok(upgrades)
 }
 
-pub async fn install_package(pkg: Package) -> Result<String> {
-    let (cmd, args) = match pkg.source {
-        Source::Apt => ("apt", vec!["install", "-y", &pkg.name]),
-        Source::Snap => ("snap", vec!["install", &pkg.name]),
-        Source::Flatpak => ("flatpak", vec!["install", "--assumeyes", &pkg.name]),
-        _ => return Ok("Invalid source".to_string()),
-    };
-    let output = AsyncCommand::new("sudo")
-        .arg(cmd)
-        .args(&args)
+/// Parses `flatpak remote-ls --updates`, tab-separated like
+/// `flatpak search` (`Name\tDescription\tApplication ID\tVersion\t...`).
+async fn list_flatpak_upgrades() -> Result<Vec<Upgrade>> {
+    let mut upgrades = Vec::new();
+    let output = AsyncCommand::new("flatpak")
+        .arg("remote-ls")
+        .arg("--updates")
         .output()
         .await
-        .context("Failed to install package")?;
+        .context("Failed to execute flatpak remote-ls --updates")?;
     if output.status.success() {
-        Ok(format!("Installed {} from {}", pkg.name, pkg.source.as_str()))
-    } else {
-        Err(anyhow::anyhow!("Failed to install {} from {}: {}", pkg.name, pkg.source.as_str(), String::from_utf8_lossy(&output.stderr)))
+        let text = String::from_utf8_lossy(&output.stdout);
+        for line in text.lines() {
+            let parts: Vec<&str> = line.split('\t').collect();
+            if parts.len() >= 3 {
+                upgrades.push(Upgrade {
+                    name: parts[2].to_string(),
+                    source: Source::Flatpak,
+                    current: String::new(),
+                    candidate: parts.get(3).unwrap_or(&"").to_string(),
+                });
+            }
+        }
     }
+    Ok(upgrades)
 }
 
-pub async fn remove_package(pkg: Package) -> Result<String> {
-    let (cmd, args) = match pkg.source {
-        Source::Apt => ("apt", vec!["remove", "-y", &pkg.name]),
-        Source::Snap => ("snap", vec!["remove", &pkg.name]),
-        Source::Flatpak => ("flatpak", vec!["uninstall", "--assumeyes", &pkg.name]),
-        _ => return Ok("Invalid source".to_string()),
+pub async fn install_package(pkg: Package, non_interactive: bool) -> Result<String> {
+    let (cmd, args): (&str, &[&str]) = match pkg.source {
+        Source::Apt => ("apt", &["install"]),
+        Source::Snap => ("snap", &["install"]),
+        Source::Flatpak => ("flatpak", &["install"]),
+        _ => return Ok(crate::fl!("invalid-source")),
     };
-    let output = AsyncCommand::new("sudo")
-        .arg(cmd)
-        .args(&args)
-        .output()
-        .await
-        .context("Failed to remove package")?;
-    if output.status.success() {
-        Ok(format!("Removed {} from {}", pkg.name, pkg.source.as_str()))
-    } else {
-        Err(anyhow::anyhow!("Failed to remove {} from {}: {}", pkg.name, pkg.source.as_str(), String::from_utf8_lossy(&output.stderr)))
+    let result = ShellCommand::new(cmd)
+        .args(args)
+        .args([pkg.name.as_str()])
+        .elevated(non_interactive)
+        .noconfirm(true)
+        .wait_success()
+        .await;
+    match result {
+        Ok(_) => Ok(crate::fl!("installed-from", "name" => pkg.name, "source" => pkg.source.as_str())),
+        Err(err) => Err(AppExitCode::InstallFailed(crate::fl!(
+            "install-failed",
+            "name" => pkg.name,
+            "source" => pkg.source.as_str(),
+            "error" => err.to_string()
+        ))
+        .into()),
     }
 }
+
+pub async fn remove_package(pkg: Package, non_interactive: bool) -> Result<String> {
+    let (cmd, args): (&str, &[&str]) = match pkg.source {
+        Source::Apt => ("apt", &["remove"]),
+        Source::Snap => ("snap", &["remove"]),
+        Source::Flatpak => ("flatpak", &["uninstall"]),
+        _ => return Ok(crate::fl!("invalid-source")),
+    };
+    let result = ShellCommand::new(cmd)
+        .args(args)
+        .args([pkg.name.as_str()])
+        .elevated(non_interactive)
+        .noconfirm(true)
+        .wait_success()
+        .await;
+    match result {
+        Ok(_) => Ok(crate::fl!("removed-from", "name" => pkg.name, "source" => pkg.source.as_str())),
+        Err(err) => Err(AppExitCode::RemoveFailed(crate::fl!(
+            "remove-failed",
+            "name" => pkg.name,
+            "source" => pkg.source.as_str(),
+            "error" => err.to_string()
+        ))
+        .into()),
+    }
+}
+
+/// Installs every package in `pkgs` in one batch per `Source`, so a
+/// multi-select install issues a single `apt install pkg1 pkg2 …` rather
+/// than one `sudo apt install` per package. All packages in a source's
+/// group share that group's outcome, since apt/snap/flatpak don't report
+/// per-package status for a combined invocation.
+pub async fn install_batch(pkgs: Vec<Package>, non_interactive: bool) -> Vec<(Package, Result<String, String>)> {
+    run_batch(pkgs, non_interactive, true).await
+}
+
+/// Removes every package in `pkgs` in one batch per `Source`, mirroring
+/// [`install_batch`].
+pub async fn remove_batch(pkgs: Vec<Package>, non_interactive: bool) -> Vec<(Package, Result<String, String>)> {
+    run_batch(pkgs, non_interactive, false).await
+}
+
+async fn run_batch(pkgs: Vec<Package>, non_interactive: bool, install: bool) -> Vec<(Package, Result<String, String>)> {
+    let mut results = Vec::new();
+    for source in [Source::Apt, Source::Snap, Source::Flatpak] {
+        let group: Vec<Package> = pkgs.iter().filter(|p| p.source == source).cloned().collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        let names: Vec<&str> = group.iter().map(|p| p.name.as_str()).collect();
+        let (cmd, action): (&str, &str) = match source {
+            Source::Apt => ("apt", if install { "install" } else { "remove" }),
+            Source::Snap => ("snap", if install { "install" } else { "remove" }),
+            Source::Flatpak => ("flatpak", if install { "install" } else { "uninstall" }),
+            Source::All => unreachable!("group is only built for Apt, Snap and Flatpak"),
+        };
+
+        let outcome = ShellCommand::new(cmd)
+            .args([action])
+            .args(names.iter().copied())
+            .elevated(non_interactive)
+            .noconfirm(true)
+            .wait_success()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+
+        for pkg in group {
+            let per_pkg = match &outcome {
+                Ok(_) if install => Ok(crate::fl!("installed-from", "name" => pkg.name.clone(), "source" => pkg.source.as_str())),
+                Ok(_) => Ok(crate::fl!("removed-from", "name" => pkg.name.clone(), "source" => pkg.source.as_str())),
+                Err(err) if install => Err(crate::fl!("install-failed", "name" => pkg.name.clone(), "source" => pkg.source.as_str(), "error" => err.clone())),
+                Err(err) => Err(crate::fl!("remove-failed", "name" => pkg.name.clone(), "source" => pkg.source.as_str(), "error" => err.clone())),
+            };
+            results.push((pkg, per_pkg));
+        }
+    }
+    results
+}
+
+/// Upgrades every package in `pkgs` (built from `Upgrade::to_package`, so
+/// `pkg.version` holds the candidate) in one batch per `Source`, using
+/// each backend's own upgrade command rather than a plain install.
+pub async fn upgrade_batch(pkgs: Vec<Package>, non_interactive: bool) -> Vec<(Package, Result<String, String>)> {
+    let mut results = Vec::new();
+    for source in [Source::Apt, Source::Snap, Source::Flatpak] {
+        let group: Vec<Package> = pkgs.iter().filter(|p| p.source == source).cloned().collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        let names: Vec<&str> = group.iter().map(|p| p.name.as_str()).collect();
+        let (cmd, args): (&str, &[&str]) = match source {
+            Source::Apt => ("apt", &["install", "--only-upgrade"]),
+            Source::Snap => ("snap", &["refresh"]),
+            Source::Flatpak => ("flatpak", &["update"]),
+            Source::All => unreachable!("group is only built for Apt, Snap and Flatpak"),
+        };
+
+        let outcome = ShellCommand::new(cmd)
+            .args(args)
+            .args(names.iter().copied())
+            .elevated(non_interactive)
+            .noconfirm(true)
+            .wait_success()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+
+        for pkg in group {
+            let per_pkg = match &outcome {
+                Ok(_) => Ok(crate::fl!("upgraded", "name" => pkg.name.clone(), "source" => pkg.source.as_str())),
+                Err(err) => Err(crate::fl!("upgrade-failed", "name" => pkg.name.clone(), "source" => pkg.source.as_str(), "error" => err.clone())),
+            };
+            results.push((pkg, per_pkg));
+        }
+    }
+    results
+}
+
+/// Handle for the background credential-refresh task started by
+/// `start_sudoloop`. Calling `stop`, or dropping the handle, ends the task
+/// as soon as it observes the shutdown signal; a `sudo -n -v` refresh
+/// already running finishes first.
+pub struct SudoLoopHandle {
+    shutdown: tokio::sync::watch::Sender<bool>,
+}
+
+impl SudoLoopHandle {
+    /// Tells the background task to stop refreshing the credential after
+    /// its current tick.
+    pub fn stop(&self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+/// Runs `sudo -v` once up front, leaving raw mode just long enough for
+/// the password prompt to render normally, then spawns a task that
+/// refreshes the cached credential with `sudo -n -v` every 60 seconds
+/// until `SudoLoopHandle::stop` is called, so a later
+/// `install_package`/`remove_package` call made with `non_interactive:
+/// true` never blocks on a prompt mid-screen.
+pub async fn start_sudoloop() -> Result<SudoLoopHandle> {
+    crossterm::terminal::disable_raw_mode().ok();
+    let status = std::process::Command::new("sudo")
+        .arg("-v")
+        .status()
+        .context("Failed to run sudo -v")?;
+    crossterm::terminal::enable_raw_mode().ok();
+    if !status.success() {
+        return Err(anyhow::anyhow!("sudo -v failed; re-run without --sudoloop or check your sudo permissions"));
+    }
+
+    let (shutdown, mut shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    let _ = AsyncCommand::new("sudo")
+                        .arg("-n")
+                        .arg("-v")
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .status()
+                        .await;
+                }
+                _ = shutdown_rx.changed() => break,
+            }
+        }
+    });
+
+    Ok(SudoLoopHandle { shutdown })
+}
@@ -0,0 +1,114 @@
+//! Local SQLite cache of search results and installed-package records,
+//! so a repeat query serves instantly before the live search resolves
+//! and "installed" status doesn't need to re-shell out to every backend.
+use crate::app::{Package, Source};
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+
+fn db_path() -> Result<PathBuf> {
+    let mut dir = dirs::cache_dir().ok_or_else(|| anyhow::anyhow!("Could not determine cache directory"))?;
+    dir.push("hpm");
+    std::fs::create_dir_all(&dir).context("Failed to create cache directory")?;
+    dir.push("cache.db");
+    Ok(dir)
+}
+
+pub fn open_db() -> Result<Connection> {
+    let conn = Connection::open(db_path()?).context("Failed to open cache database")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS packages (
+            name TEXT NOT NULL,
+            source TEXT NOT NULL,
+            version TEXT NOT NULL,
+            description TEXT NOT NULL,
+            PRIMARY KEY (name, source)
+        );
+        CREATE TABLE IF NOT EXISTS installed (
+            name TEXT NOT NULL,
+            source TEXT NOT NULL,
+            installed_at TEXT NOT NULL,
+            PRIMARY KEY (name, source)
+        );",
+    )?;
+    Ok(conn)
+}
+
+fn source_from_str(s: &str) -> Source {
+    match s {
+        "APT" => Source::Apt,
+        "SNAP" => Source::Snap,
+        "FLATPAK" => Source::Flatpak,
+        _ => Source::All,
+    }
+}
+
+/// Caches every package from a fresh search so the next matching query
+/// can serve instantly while a background refresh runs.
+pub fn cache_packages(conn: &Connection, packages: &[Package]) -> Result<()> {
+    for pkg in packages {
+        conn.execute(
+            "INSERT INTO packages (name, source, version, description) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(name, source) DO UPDATE SET version = excluded.version, description = excluded.description",
+            params![pkg.name, pkg.source.as_str(), pkg.version, pkg.description],
+        )?;
+    }
+    Ok(())
+}
+
+/// Rows already cached whose name matches `query`, for instant results
+/// before the live search resolves.
+pub fn search_cached(conn: &Connection, query: &str) -> Result<Vec<Package>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, source, version, description FROM packages WHERE name LIKE ?1 ORDER BY name",
+    )?;
+    let like = format!("%{}%", query);
+    let rows = stmt.query_map(params![like], |row| {
+        Ok(Package {
+            name: row.get(0)?,
+            source: source_from_str(&row.get::<_, String>(1)?),
+            version: row.get(2)?,
+            description: row.get(3)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+/// Records a successful install so the TUI can show an "installed"
+/// marker and list installed packages without re-shelling out.
+pub fn record_installed(conn: &Connection, pkg: &Package, timestamp: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO installed (name, source, installed_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(name, source) DO UPDATE SET installed_at = excluded.installed_at",
+        params![pkg.name, pkg.source.as_str(), timestamp],
+    )?;
+    Ok(())
+}
+
+/// Drops the installed record for `pkg`, called after a successful
+/// `remove_package`.
+pub fn forget_installed(conn: &Connection, pkg: &Package) -> Result<()> {
+    conn.execute(
+        "DELETE FROM installed WHERE name = ?1 AND source = ?2",
+        params![pkg.name, pkg.source.as_str()],
+    )?;
+    Ok(())
+}
+
+/// Every package recorded as installed, most recent first.
+pub fn list_installed(conn: &Connection) -> Result<Vec<Package>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.name, i.source, COALESCE(p.version, ''), COALESCE(p.description, '')
+         FROM installed i LEFT JOIN packages p ON p.name = i.name AND p.source = i.source
+         ORDER BY i.installed_at DESC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Package {
+            name: row.get(0)?,
+            source: source_from_str(&row.get::<_, String>(1)?),
+            version: row.get(2)?,
+            description: row.get(3)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>().map_err(Into::into)
+}
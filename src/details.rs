@@ -0,0 +1,137 @@
+//! Per-source "show me everything about this package" queries. One typed
+//! builder per backend composes the right inspection command and parses
+//! its own output into a shared `PackageDetails`, so `Command::new(...)`
+//! calls for this don't end up scattered across `app.rs`/`main.rs`.
+use crate::app::{Package, Source};
+use anyhow::{Context, Result};
+use tokio::process::Command as AsyncCommand;
+
+#[derive(Clone, Default)]
+pub struct PackageDetails {
+    pub name: String,
+    pub version: String,
+    pub size: String,
+    pub homepage: String,
+    pub license: String,
+    pub description: String,
+}
+
+impl PackageDetails {
+    fn named(name: &str) -> Self {
+        PackageDetails {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Composes the command that inspects one package and folds its stdout
+/// into a `PackageDetails`.
+trait DetailsQuery {
+    fn build(&self) -> (&'static str, Vec<String>);
+    fn parse(&self, stdout: &str) -> PackageDetails;
+}
+
+struct AptDetailsQuery {
+    name: String,
+}
+
+impl DetailsQuery for AptDetailsQuery {
+    fn build(&self) -> (&'static str, Vec<String>) {
+        ("apt-cache", vec!["show".to_string(), self.name.clone()])
+    }
+
+    fn parse(&self, stdout: &str) -> PackageDetails {
+        let mut details = PackageDetails::named(&self.name);
+        for line in stdout.lines() {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            match key.trim() {
+                "Version" => details.version = value.to_string(),
+                "Installed-Size" => details.size = format!("{} KB", value),
+                "Homepage" => details.homepage = value.to_string(),
+                "Description" | "Description-en" => details.description = value.to_string(),
+                _ => {}
+            }
+        }
+        details
+    }
+}
+
+struct SnapDetailsQuery {
+    name: String,
+}
+
+impl DetailsQuery for SnapDetailsQuery {
+    fn build(&self) -> (&'static str, Vec<String>) {
+        ("snap", vec!["info".to_string(), self.name.clone()])
+    }
+
+    fn parse(&self, stdout: &str) -> PackageDetails {
+        let mut details = PackageDetails::named(&self.name);
+        for line in stdout.lines() {
+            let trimmed = line.trim_start();
+            if let Some(value) = trimmed.strip_prefix("summary:") {
+                details.description = value.trim().to_string();
+            } else if let Some(value) = trimmed.strip_prefix("license:") {
+                details.license = value.trim().to_string();
+            } else if let Some(value) = trimmed.strip_prefix("installed:") {
+                let fields: Vec<&str> = value.split_whitespace().collect();
+                if let Some(version) = fields.first() {
+                    details.version = version.to_string();
+                }
+                if let Some(size) = fields.iter().find(|f| f.ends_with("kB") || f.ends_with("MB") || f.ends_with("GB")) {
+                    details.size = size.to_string();
+                }
+            }
+        }
+        details
+    }
+}
+
+struct FlatpakDetailsQuery {
+    name: String,
+}
+
+impl DetailsQuery for FlatpakDetailsQuery {
+    fn build(&self) -> (&'static str, Vec<String>) {
+        ("flatpak", vec!["info".to_string(), self.name.clone()])
+    }
+
+    fn parse(&self, stdout: &str) -> PackageDetails {
+        let mut details = PackageDetails::named(&self.name);
+        for line in stdout.lines() {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            match key.trim() {
+                "Version" => details.version = value.to_string(),
+                "Installed" => details.size = value.to_string(),
+                "License" => details.license = value.to_string(),
+                _ => {}
+            }
+        }
+        details
+    }
+}
+
+/// Picks the right builder for `pkg.source`, runs its command, and
+/// returns the parsed details.
+pub async fn fetch_details(pkg: &Package) -> Result<PackageDetails> {
+    let query: Box<dyn DetailsQuery> = match pkg.source {
+        Source::Apt => Box::new(AptDetailsQuery { name: pkg.name.clone() }),
+        Source::Snap => Box::new(SnapDetailsQuery { name: pkg.name.clone() }),
+        Source::Flatpak => Box::new(FlatpakDetailsQuery { name: pkg.name.clone() }),
+        Source::All => return Err(anyhow::anyhow!(crate::fl!("invalid-source"))),
+    };
+
+    let (program, args) = query.build();
+    let output = AsyncCommand::new(program)
+        .args(&args)
+        .output()
+        .await
+        .context("Failed to run package details command")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
+    Ok(query.parse(&String::from_utf8_lossy(&output.stdout)))
+}
@@ -0,0 +1,91 @@
+//! Fluent-backed translations for every user-facing string in the TUI.
+//! Locale is picked once at startup (`--lang`, falling back to
+//! `$LC_MESSAGES`, then `$LANG`, then `en-US`) and cached in a global bundle; `fl!` is the
+//! only thing the rest of the crate needs to know about.
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use std::sync::{Mutex, OnceLock};
+use unic_langid::LanguageIdentifier;
+
+static EN_US: &str = include_str!("../locales/en-US/main.ftl");
+static PL_PL: &str = include_str!("../locales/pl-PL/main.ftl");
+
+static BUNDLE: OnceLock<Mutex<FluentBundle<FluentResource>>> = OnceLock::new();
+
+fn build_bundle(locale: &str, ftl: &str) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = locale.parse().unwrap_or_else(|_| "en-US".parse().unwrap());
+    let mut bundle = FluentBundle::new(vec![langid]);
+    // The terminal has no bidi renderer, so the default isolate marks
+    // around interpolated values would show up as stray blanks/boxes.
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(ftl.to_string())
+        .unwrap_or_else(|(res, _errors)| res);
+    bundle
+        .add_resource(resource)
+        .expect("built-in .ftl resources must not define duplicate messages");
+    bundle
+}
+
+/// Strips a trailing encoding/variant (`pl_PL.UTF-8` -> `pl-PL`) and swaps
+/// `_` for `-` so both `$LANG` and `--lang` can be matched the same way.
+fn normalize_locale(raw: &str) -> String {
+    raw.split('.').next().unwrap_or(raw).replace('_', "-")
+}
+
+fn resolve(locale: &str) -> (&'static str, &'static str) {
+    match locale {
+        l if l.eq_ignore_ascii_case("pl-PL") || l.eq_ignore_ascii_case("pl") => ("pl-PL", PL_PL),
+        _ => ("en-US", EN_US),
+    }
+}
+
+/// Picks the bundle for this run. `lang_override` wins (the `--lang`
+/// flag), otherwise `$LC_MESSAGES` is consulted, otherwise `$LANG`,
+/// otherwise `en-US` is used. Must be called once before any `fl!`
+/// lookup; later calls are no-ops.
+pub fn init(lang_override: Option<&str>) {
+    let requested = lang_override
+        .map(|s| s.to_string())
+        .or_else(|| std::env::var("LC_MESSAGES").ok())
+        .or_else(|| std::env::var("LANG").ok())
+        .map(|s| normalize_locale(&s))
+        .unwrap_or_else(|| "en-US".to_string());
+    let (locale, ftl) = resolve(&requested);
+    let _ = BUNDLE.set(Mutex::new(build_bundle(locale, ftl)));
+}
+
+/// Looks up `id` in the active bundle and formats it with `args`, using
+/// the `en-US` bundle if `init` was never called. Falls back to the bare
+/// message id if the id or its value is missing, so a typo'd id shows up
+/// instead of crashing the TUI.
+pub fn translate(id: &str, args: &[(&str, String)]) -> String {
+    let bundle = BUNDLE.get_or_init(|| Mutex::new(build_bundle("en-US", EN_US)));
+    let bundle = bundle.lock().unwrap();
+
+    let Some(message) = bundle.get_message(id) else {
+        return id.to_string();
+    };
+    let Some(pattern) = message.value() else {
+        return id.to_string();
+    };
+
+    let mut fargs = FluentArgs::new();
+    for (key, value) in args {
+        fargs.set(*key, FluentValue::from(value.clone()));
+    }
+
+    let mut errors = Vec::new();
+    let formatted = bundle.format_pattern(pattern, Some(&fargs), &mut errors);
+    formatted.into_owned()
+}
+
+/// Looks up a Fluent message by id, optionally interpolating named
+/// arguments: `fl!("installed-from", "name" => pkg.name, "source" => src)`.
+#[macro_export]
+macro_rules! fl {
+    ($id:expr) => {
+        $crate::i18n::translate($id, &[])
+    };
+    ($id:expr, $($key:expr => $val:expr),+ $(,)?) => {
+        $crate::i18n::translate($id, &[$(($key, $val.to_string())),+])
+    };
+}
@@ -1,4 +1,13 @@
-use anyhow::{Context, Result};
+mod app;
+mod database;
+mod details;
+mod i18n;
+mod version;
+
+use crate::app::{search_packages, start_sudoloop, App, InputMode, Mode, Package, Source, Upgrade};
+use crate::details::PackageDetails;
+use crate::fl;
+use anyhow::Result;
 use clap::Parser;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
@@ -11,12 +20,13 @@ use ratatui::{
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame, Terminal,
 };
 use std::io;
-use std::process::Command;
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::time::interval;
 
 #[derive(Parser, Debug)]
 #[command(name = "hpm")]
@@ -25,203 +35,63 @@ struct Args {
     /// Initial search query
     #[arg(short, long)]
     query: Option<String>,
-}
-
-enum InputMode {
-    Normal,
-    Editing,
-}
-
-enum Message {
-    Quit,
-    Input(KeyCode),
-}
-
-struct App {
-    input: String,
-    input_mode: InputMode,
-    packages: Vec<Package>,
-    package_list_state: ListState,
-    selected_source: Source,
-    message: String,
-}
 
-#[derive(Clone)]
-struct Package {
-    name: String,
-    source: Source,
-    description: String,
-}
+    /// Keep a sudo credential cached in the background so installs never
+    /// block on a password prompt mid-screen
+    #[arg(long)]
+    sudoloop: bool,
 
-#[derive(Clone, PartialEq)]
-enum Source {
-    Apt,
-    Snap,
-    Flatpak,
+    /// UI locale, e.g. "en-US" or "pl-PL" (defaults to `$LC_MESSAGES`, then `$LANG`, then en-US)
+    #[arg(long)]
+    lang: Option<String>,
 }
 
-impl Source {
-    fn as_str(&self) -> &'static str {
-        match self {
-            Source::Apt => "APT",
-            Source::Snap => "SNAP",
-            Source::Flatpak => "FLATPAK",
-        }
-    }
+/// Results delivered back from spawned backend tasks to the event loop,
+/// since `Command::output` behind a blocking call would freeze the
+/// whole TUI while `apt-cache search` or a multi-minute `apt install`
+/// runs.
+enum Message {
+    SearchResult(Vec<Package>),
+    UpgradeList(Vec<Upgrade>),
+    Details(Result<PackageDetails, String>),
+    OpProgress(String),
+    OpDone(Result<String, String>),
+    Installed(Package),
+    Removed(Package),
 }
 
-impl App {
-    fn new() -> App {
-        App {
-            input: String::new(),
-            input_mode: InputMode::Normal,
-            packages: Vec::new(),
-            package_list_state: ListState::default(),
-            selected_source: Source::Apt,
-            message: String::new(),
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    i18n::init(args.lang.as_deref());
+    let mut app = App::new();
+    if let Ok(conn) = database::open_db() {
+        if let Ok(installed) = database::list_installed(&conn) {
+            app.installed = installed
+                .iter()
+                .map(|p| (p.name.clone(), p.source.as_str()))
+                .collect();
         }
     }
-
-    fn search_packages(&mut self) -> Result<()> {
-        self.packages.clear();
-        self.message.clear();
-
-        if self.input.is_empty() {
-            self.message = "Enter a search query.".to_string();
-            return Ok(());
-        }
-
-        // Search APT
-        let apt_output = Command::new("apt-cache")
-            .arg("search")
-            .arg("--names-only")
-            .arg(&self.input)
-            .output()
-            .context("Failed to execute apt-cache search")?;
-        if apt_output.status.success() {
-            let apt_str = String::from_utf8_lossy(&apt_output.stdout);
-            for line in apt_str.lines() {
-                if let Some((name, desc)) = line.split_once(" - ") {
-                    self.packages.push(Package {
-                        name: name.to_string(),
-                        source: Source::Apt,
-                        description: desc.to_string(),
-                    });
-                }
-            }
-        }
-
-        // Search Snap
-        let snap_output = Command::new("snap")
-            .arg("find")
-            .arg(&self.input)
-            .output()
-            .context("Failed to execute snap find")?;
-        if snap_output.status.success() {
-            let snap_str = String::from_utf8_lossy(&snap_output.stdout);
-            for line in snap_str.lines().skip(1) {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 5 {
-                    let name = parts[0].to_string();
-                    let description = parts[4..].join(" ");
-                    self.packages.push(Package {
-                        name,
-                        source: Source::Snap,
-                        description,
-                    });
-                }
-            }
-        }
-
-        // Search Flatpak
-        let flatpak_output = Command::new("flatpak")
-            .arg("search")
-            .arg(&self.input)
-            .output()
-            .context("Failed to execute flatpak search")?;
-        if flatpak_output.status.success() {
-            let flatpak_str = String::from_utf8_lossy(&flatpak_output.stdout);
-            for line in flatpak_str.lines().skip(1) {
-                let parts: Vec<&str> = line.split('\t').collect();
-                if parts.len() >= 4 {
-                    let name = parts[2].to_string(); // Application ID for install
-                    let description = format!("{} - {}", parts[0], parts[1]);
-                    self.packages.push(Package {
-                        name,
-                        source: Source::Flatpak,
-                        description,
-                    });
-                }
+    app.sudoloop_enabled = args.sudoloop;
+    if let Some(query) = args.query {
+        app.input = query.clone();
+        app.packages = match search_packages(query).await {
+            Ok(packages) => packages,
+            Err(err) => {
+                let code = err.downcast_ref::<app::AppExitCode>().map(i32::from).unwrap_or(1);
+                eprintln!("{}", err);
+                std::process::exit(code);
             }
-        }
-
-        // Filter by selected source if needed, but we collect all and filter in UI
-        if self.packages.is_empty() {
-            self.message = "No packages found.".to_string();
+        };
+        if app.packages.is_empty() {
+            app.message = fl!("no-packages-found");
         } else {
-            self.package_list_state.select(Some(0));
+            app.package_list_state.select(Some(0));
         }
-        Ok(())
-    }
-
-    fn install_package(&mut self) -> Result<()> {
-        if let Some(selected) = self.package_list_state.selected() {
-            if let Some(pkg) = self.packages.get(selected) {
-                let (cmd, args) = match pkg.source {
-                    Source::Apt => ("apt", vec!["install", "-y", &pkg.name]),
-                    Source::Snap => ("snap", vec!["install", &pkg.name]),
-                    Source::Flatpak => ("flatpak", vec!["install", "-y", &pkg.name]),
-                };
-
-                let status = Command::new("sudo")
-                    .arg(cmd)
-                    .args(&args)
-                    .status()
-                    .context("Failed to install package")?;
-
-                self.message = if status.success() {
-                    format!("Installed {} from {}", pkg.name, pkg.source.as_str())
-                } else {
-                    format!("Failed to install {} from {}", pkg.name, pkg.source.as_str())
-                };
-            }
-        }
-        Ok(())
-    }
-
-    fn remove_package(&mut self) -> Result<()> {
-        if let Some(selected) = self.package_list_state.selected() {
-            if let Some(pkg) = self.packages.get(selected) {
-                let (cmd, args) = match pkg.source {
-                    Source::Apt => ("apt", vec!["remove", "-y", &pkg.name]),
-                    Source::Snap => ("snap", vec!["remove", &pkg.name]),
-                    Source::Flatpak => ("flatpak", vec!["uninstall", "-y", &pkg.name]),
-                };
-
-                let status = Command::new("sudo")
-                    .arg(cmd)
-                    .args(&args)
-                    .status()
-                    .context("Failed to remove package")?;
-
-                self.message = if status.success() {
-                    format!("Removed {} from {}", pkg.name, pkg.source.as_str())
-                } else {
-                    format!("Failed to remove {} from {}", pkg.name, pkg.source.as_str())
-                };
-            }
+        if let Ok(conn) = database::open_db() {
+            let _ = database::cache_packages(&conn, &app.packages);
         }
-        Ok(())
-    }
-}
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    let mut app = App::new();
-    if let Some(query) = args.query {
-        app.input = query;
-        app.search_packages()?;
     }
 
     enable_raw_mode()?;
@@ -252,71 +122,323 @@ async fn main() -> Result<()> {
 async fn run_app(
     terminal: &mut Terminal<impl Backend>,
     mut app: App,
-    _tx: mpsc::Sender<Message>,
-    _rx: &mut mpsc::Receiver<Message>,
+    tx: mpsc::Sender<Message>,
+    rx: &mut mpsc::Receiver<Message>,
 ) -> Result<()> {
     let mut event_stream = event::EventStream::new();
+    let mut spinner = interval(Duration::from_millis(150));
 
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
 
-        if let Some(Ok(event)) = event_stream.next().await {
-            if let Event::Key(key) = event {
-                match app.input_mode {
-                    InputMode::Normal => match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Char('e') => app.input_mode = InputMode::Editing,
-                        KeyCode::Down => {
-                            if let Some(selected) = app.package_list_state.selected() {
-                                let len = app
-                                    .packages
-                                    .iter()
-                                    .filter(|p| p.source == app.selected_source)
-                                    .count();
-                                if selected + 1 < len {
-                                    app.package_list_state.select(Some(selected + 1));
-                                }
-                            }
-                        }
-                        KeyCode::Up => {
-                            if let Some(selected) = app.package_list_state.selected() {
-                                if selected > 0 {
-                                    app.package_list_state.select(Some(selected - 1));
-                                }
-                            }
-                        }
-                        KeyCode::Enter => {
-                            app.search_packages()?;
-                        }
-                        KeyCode::Char('i') => {
-                            app.install_package()?;
+        tokio::select! {
+            Some(Ok(event)) = event_stream.next() => {
+                if let Event::Key(key) = event {
+                    if !handle_key(&mut app, key.code, &tx).await {
+                        return Ok(());
+                    }
+                }
+            }
+            Some(msg) = rx.recv() => {
+                match msg {
+                    Message::SearchResult(packages) => {
+                        app.busy = false;
+                        app.cache_results(&packages);
+                        app.showing_cached = false;
+                        app.packages = packages;
+                        if app.packages.is_empty() {
+                            app.message = fl!("no-packages-found");
+                        } else {
+                            app.message.clear();
+                            app.package_list_state.select(Some(0));
                         }
-                        KeyCode::Char('r') => {
-                            app.remove_package()?;
+                    }
+                    Message::UpgradeList(upgrades) => {
+                        app.busy = false;
+                        app.message = if upgrades.is_empty() {
+                            fl!("no-upgrades-found")
+                        } else {
+                            app.package_list_state.select(Some(0));
+                            String::new()
+                        };
+                        app.upgrades = upgrades;
+                    }
+                    Message::Details(result) => {
+                        app.busy = false;
+                        match result {
+                            Ok(details) => app.details = Some(details),
+                            Err(err) => app.message = fl!("error-prefix", "error" => err),
                         }
-                        KeyCode::Char('a') => app.selected_source = Source::Apt,
-                        KeyCode::Char('s') => app.selected_source = Source::Snap,
-                        KeyCode::Char('f') => app.selected_source = Source::Flatpak,
-                        _ => {}
-                    },
-                    InputMode::Editing => match key.code {
-                        KeyCode::Enter => {
-                            app.search_packages()?;
-                            app.input_mode = InputMode::Normal;
+                    }
+                    Message::OpProgress(text) => app.message = text,
+                    Message::OpDone(result) => {
+                        app.busy = false;
+                        if let Some(handle) = app.sudoloop.take() {
+                            handle.stop();
                         }
-                        KeyCode::Char(c) => app.input.push(c),
-                        KeyCode::Backspace => {
-                            app.input.pop();
+                        // Each package's own result already reached `app.message`
+                        // via `OpProgress` as the batch streamed in; only an
+                        // overall failure (not surfaced per-package) replaces it.
+                        if let Err(err) = result {
+                            app.message = fl!("error-prefix", "error" => err);
                         }
-                        KeyCode::Esc => app.input_mode = InputMode::Normal,
-                        _ => {}
-                    },
+                    }
+                    Message::Installed(pkg) => {
+                        app.installed.insert((pkg.name, pkg.source.as_str()));
+                    }
+                    Message::Removed(pkg) => {
+                        app.installed.remove(&(pkg.name, pkg.source.as_str()));
+                    }
                 }
             }
+            _ = spinner.tick() => {
+                if app.busy {
+                    app.dot_count = (app.dot_count + 1) % 4;
+                }
+            }
+        }
+    }
+}
+
+/// Handles one key event; returns `false` when the app should quit.
+async fn handle_key(app: &mut App, code: KeyCode, tx: &mpsc::Sender<Message>) -> bool {
+    match app.input_mode {
+        InputMode::Normal => match code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('e') => app.input_mode = InputMode::Editing,
+            KeyCode::Down => {
+                let len = match app.mode {
+                    Mode::Browse => app.get_filtered_packages().len(),
+                    Mode::Upgradable => app.upgrades.len(),
+                };
+                if let Some(selected) = app.package_list_state.selected() {
+                    if selected + 1 < len {
+                        app.package_list_state.select(Some(selected + 1));
+                    }
+                }
+            }
+            KeyCode::Up => {
+                if let Some(selected) = app.package_list_state.selected() {
+                    if selected > 0 {
+                        app.package_list_state.select(Some(selected - 1));
+                    }
+                }
+            }
+            KeyCode::Enter => spawn_search(app, tx.clone()),
+            KeyCode::Char('i') => match app.mode {
+                Mode::Browse => spawn_op(app, tx.clone(), true).await,
+                Mode::Upgradable => spawn_upgrade(app, tx.clone()).await,
+            },
+            KeyCode::Char('r') => {
+                if app.mode == Mode::Browse {
+                    spawn_op(app, tx.clone(), false).await;
+                }
+            }
+            KeyCode::Char('a') => app.selected_source = Source::Apt,
+            KeyCode::Char('A') => {
+                if app.mode == Mode::Upgradable {
+                    app.select_all_upgrades();
+                }
+            }
+            KeyCode::Char('s') => app.selected_source = Source::Snap,
+            KeyCode::Char('f') => app.selected_source = Source::Flatpak,
+            KeyCode::Char('l') => show_installed(app),
+            KeyCode::Char('u') => toggle_upgradable_mode(app, tx.clone()),
+            KeyCode::Char('o') => spawn_details(app, tx.clone()),
+            KeyCode::Char(' ') => app.toggle_selected(),
+            KeyCode::Esc => app.details = None,
+            _ => {}
+        },
+        InputMode::Editing => match code {
+            KeyCode::Enter => {
+                app.input_mode = InputMode::Normal;
+                spawn_search(app, tx.clone());
+            }
+            KeyCode::Char(c) => app.input.push(c),
+            KeyCode::Backspace => {
+                app.input.pop();
+            }
+            KeyCode::Esc => app.input_mode = InputMode::Normal,
+            _ => {}
+        },
+    }
+    true
+}
+
+/// Spawns `search_packages` in the background and reports back over
+/// `tx` instead of blocking the event loop on `apt-cache`/`snap`/
+/// `flatpak`. Cached rows matching the query are shown immediately so
+/// the list isn't empty while the live search is in flight.
+fn spawn_search(app: &mut App, tx: mpsc::Sender<Message>) {
+    app.message.clear();
+    if app.input.is_empty() {
+        app.message = fl!("enter-search-query");
+        return;
+    }
+    let cached = app.search_cache(&app.input);
+    if !cached.is_empty() {
+        app.packages = cached;
+        app.package_list_state.select(Some(0));
+        app.showing_cached = true;
+    }
+    app.busy = true;
+    let input = app.input.clone();
+    tokio::spawn(async move {
+        let msg = match search_packages(input).await {
+            Ok(packages) => Message::SearchResult(packages),
+            Err(err) => Message::OpDone(Err(err.to_string())),
+        };
+        let _ = tx.send(msg).await;
+    });
+}
+
+/// Replaces the current list with every package recorded as installed,
+/// read straight from the local cache.
+fn show_installed(app: &mut App) {
+    match database::open_db().and_then(|conn| database::list_installed(&conn)) {
+        Ok(packages) => {
+            app.message = if packages.is_empty() {
+                fl!("no-installed-packages")
+            } else {
+                app.package_list_state.select(Some(0));
+                fl!("showing-installed")
+            };
+            app.packages = packages;
+        }
+        Err(err) => app.message = fl!("error-prefix", "error" => err),
+    }
+}
+
+/// Toggles between `Mode::Browse` and `Mode::Upgradable`; entering the
+/// latter kicks off a fresh `list_upgradable` scan.
+fn toggle_upgradable_mode(app: &mut App, tx: mpsc::Sender<Message>) {
+    app.selected.clear();
+    app.package_list_state.select(None);
+    app.mode = match app.mode {
+        Mode::Browse => Mode::Upgradable,
+        Mode::Upgradable => Mode::Browse,
+    };
+    if app.mode == Mode::Upgradable {
+        app.message = fl!("checking-upgrades");
+        app.busy = true;
+        tokio::spawn(async move {
+            let msg = match app::list_upgradable().await {
+                Ok(upgrades) => Message::UpgradeList(upgrades),
+                Err(err) => Message::OpDone(Err(err.to_string())),
+            };
+            let _ = tx.send(msg).await;
+        });
+    } else {
+        app.package_list_state.select(Some(0));
+    }
+}
+
+/// Starts a [`app::SudoLoopHandle`] for the install/remove/upgrade about to
+/// run, if `--sudoloop` was passed and one isn't already active. A failed
+/// `sudo -v` just leaves `app.sudoloop` at `None`, which falls back to
+/// interactive sudo the same as if `--sudoloop` had never been passed.
+async fn prime_sudoloop(app: &mut App) {
+    if app.sudoloop_enabled && app.sudoloop.is_none() {
+        match start_sudoloop().await {
+            Ok(handle) => app.sudoloop = Some(handle),
+            Err(err) => app.message = fl!("error-prefix", "error" => err.to_string()),
         }
     }
 }
 
+/// Upgrades every checked row in `app.upgrades` (or just the highlighted
+/// one), same streaming pattern as `spawn_op`.
+async fn spawn_upgrade(app: &mut App, tx: mpsc::Sender<Message>) {
+    let pkgs = app.selected_upgrades();
+    if pkgs.is_empty() {
+        return;
+    }
+    app.selected.clear();
+    app.busy = true;
+    prime_sudoloop(app).await;
+    let non_interactive = app.sudoloop.is_some();
+    tokio::spawn(async move {
+        let results = app::upgrade_batch(pkgs, non_interactive).await;
+        for (_, result) in results {
+            let progress = match result {
+                Ok(text) => text,
+                Err(err) => err,
+            };
+            let _ = tx.send(Message::OpProgress(progress)).await;
+        }
+        let _ = tx.send(Message::OpDone(Ok(String::new()))).await;
+    });
+}
+
+/// Fetches and opens the details popup for the highlighted row, in
+/// either mode.
+fn spawn_details(app: &mut App, tx: mpsc::Sender<Message>) {
+    let pkg = match app.mode {
+        Mode::Browse => app.selected_package(),
+        Mode::Upgradable => app.package_list_state.selected().and_then(|i| app.upgrades.get(i)).map(|u| Package {
+            name: u.name.clone(),
+            source: u.source.clone(),
+            description: String::new(),
+            version: u.candidate.clone(),
+        }),
+    };
+    let Some(pkg) = pkg else { return };
+    app.busy = true;
+    tokio::spawn(async move {
+        let result = details::fetch_details(&pkg).await.map_err(|e| e.to_string());
+        let _ = tx.send(Message::Details(result)).await;
+    });
+}
+
+/// Spawns an install (`install == true`) or remove for every checked
+/// package (or just the highlighted one, if nothing is checked),
+/// streaming each package's result into the status area as it lands.
+async fn spawn_op(app: &mut App, tx: mpsc::Sender<Message>, install: bool) {
+    let pkgs = app.selected_packages();
+    if pkgs.is_empty() {
+        return;
+    }
+    app.selected.clear();
+    app.busy = true;
+    prime_sudoloop(app).await;
+    let non_interactive = app.sudoloop.is_some();
+    tokio::spawn(async move {
+        let results = if install {
+            app::install_batch(pkgs, non_interactive).await
+        } else {
+            app::remove_batch(pkgs, non_interactive).await
+        };
+        for (pkg, result) in results {
+            let progress = match &result {
+                Ok(text) => text.clone(),
+                Err(err) => err.clone(),
+            };
+            let _ = tx.send(Message::OpProgress(progress)).await;
+            if result.is_ok() {
+                if let Ok(conn) = database::open_db() {
+                    let recorded = if install {
+                        database::record_installed(&conn, &pkg, &timestamp_now())
+                    } else {
+                        database::forget_installed(&conn, &pkg)
+                    };
+                    let _ = recorded;
+                }
+                let _ = tx
+                    .send(if install { Message::Installed(pkg) } else { Message::Removed(pkg) })
+                    .await;
+            }
+        }
+        let _ = tx.send(Message::OpDone(Ok(String::new()))).await;
+    });
+}
+
+fn timestamp_now() -> String {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs().to_string())
+        .unwrap_or_default()
+}
+
 fn ui(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -325,35 +447,26 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     let (msg, style) = match app.input_mode {
         InputMode::Normal => (
-            vec![
-                Span::raw("Press "),
-                Span::styled("q", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to exit, "),
-                Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to edit query, "),
-                Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to search."),
-            ],
+            vec![Span::raw(fl!("help-normal"))],
             Style::default().add_modifier(Modifier::RAPID_BLINK),
         ),
         InputMode::Editing => (
-            vec![
-                Span::raw("Press "),
-                Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to cancel, "),
-                Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to search."),
-            ],
+            vec![Span::raw(fl!("help-editing"))],
             Style::default(),
         ),
     };
 
     let mut text = vec![Line::from(msg)];
+    if app.sudoloop.is_some() {
+        text.push(Line::from(Span::styled(fl!("sudoloop-active"), Style::default().fg(Color::Cyan))));
+    }
     if !app.message.is_empty() {
-        text.push(Line::from(Span::styled(
-            app.message.clone(),
-            Style::default().fg(Color::Yellow),
-        )));
+        let status = if app.busy {
+            format!("{}{}", app.message, ".".repeat(app.dot_count))
+        } else {
+            app.message.clone()
+        };
+        text.push(Line::from(Span::styled(status, Style::default().fg(Color::Yellow))));
     }
     let help_message = Paragraph::new(text).style(style);
     f.render_widget(help_message, chunks[2]);
@@ -363,7 +476,7 @@ fn ui(f: &mut Frame, app: &mut App) {
             InputMode::Normal => Style::default(),
             InputMode::Editing => Style::default().fg(Color::Yellow),
         })
-        .block(Block::default().borders(Borders::ALL).title("Search Query"));
+        .block(Block::default().borders(Borders::ALL).title(fl!("search-query-title")));
     f.render_widget(input, chunks[0]);
 
     if let InputMode::Editing = app.input_mode {
@@ -372,47 +485,97 @@ fn ui(f: &mut Frame, app: &mut App) {
         f.set_cursor_position((cursor_x, cursor_y));
     }
 
-    let filtered_packages: Vec<Package> = app
-        .packages
-        .iter()
-        .filter(|p| p.source == app.selected_source)
-        .cloned()
-        .collect();
-
-    let items: Vec<ListItem> = filtered_packages
-        .iter()
-        .map(|p| {
-            ListItem::new(Line::from(vec![
-                Span::styled(&p.name, Style::default().fg(Color::Green)),
-                Span::raw(" - "),
-                Span::raw(&p.description),
-            ]))
-        })
-        .collect();
+    let (items, title): (Vec<ListItem>, String) = match app.mode {
+        Mode::Browse => {
+            let filtered_packages: Vec<Package> = app.get_filtered_packages();
+            let items = filtered_packages
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let check = if app.selected.contains(&i) { "[x] " } else { "[ ] " };
+                    let marker = if app.is_installed(p) { "[installed] " } else { "" };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(check, Style::default().fg(Color::Magenta)),
+                        Span::styled(marker, Style::default().fg(Color::Cyan)),
+                        Span::styled(&p.name, Style::default().fg(Color::Green)),
+                        Span::raw(" - "),
+                        Span::raw(&p.description),
+                    ]))
+                })
+                .collect();
+            let mut title = fl!("packages-title", "source" => app.selected_source.as_str());
+            if app.showing_cached {
+                title.push(' ');
+                title.push_str(&fl!("cached-marker"));
+            }
+            (items, title)
+        }
+        Mode::Upgradable => {
+            let items = app
+                .upgrades
+                .iter()
+                .enumerate()
+                .map(|(i, u)| {
+                    let check = if app.selected.contains(&i) { "[x] " } else { "[ ] " };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(check, Style::default().fg(Color::Magenta)),
+                        Span::styled(u.name.as_str(), Style::default().fg(Color::Green)),
+                        Span::raw(format!(" [{}] ", u.source.as_str())),
+                        Span::raw(&u.current),
+                        Span::raw(" -> "),
+                        Span::styled(&u.candidate, Style::default().fg(Color::Yellow)),
+                    ]))
+                })
+                .collect();
+            (items, fl!("upgradable-title"))
+        }
+    };
 
     let list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(format!(
-                    "Packages [{}] (a/s/f to switch, i:install, r:remove)",
-                    app.selected_source.as_str()
-                )),
-        )
+        .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
 
     let mut state = ListState::default();
     state.select(app.package_list_state.selected());
-    if !filtered_packages.is_empty() {
-        let offset = app
-            .packages
-            .iter()
-            .filter(|p| p.source == app.selected_source)
-            .take(app.package_list_state.selected().unwrap_or(0))
-            .count();
-        state.select(Some(offset));
-    }
 
     f.render_stateful_widget(list, chunks[1], &mut state);
+
+    if let Some(details) = &app.details {
+        let popup = centered_rect(70, 60, f.area());
+        let lines = vec![
+            Line::from(Span::styled(details.name.as_str(), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))),
+            Line::from(fl!("details-version", "value" => details.version.as_str())),
+            Line::from(fl!("details-size", "value" => details.size.as_str())),
+            Line::from(fl!("details-homepage", "value" => details.homepage.as_str())),
+            Line::from(fl!("details-license", "value" => details.license.as_str())),
+            Line::from(fl!("details-description", "value" => details.description.as_str())),
+        ];
+        let popup_widget = Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title(fl!("details-title")));
+        f.render_widget(Clear, popup);
+        f.render_widget(popup_widget, popup);
+    }
+}
+
+/// A `percent_x`% by `percent_y`% `Rect` centered in `area`, the usual ratatui
+/// pattern for modal-ish overlays.
+fn centered_rect(percent_x: u16, percent_y: u16, area: ratatui::layout::Rect) -> ratatui::layout::Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
 }
@@ -0,0 +1,123 @@
+//! Debian-style version comparison (the same rules `dpkg --compare-versions`
+//! uses), so the upgradable view can tell a genuinely newer APT candidate
+//! from a sidegrade without shelling out to `dpkg` just to ask.
+use std::cmp::Ordering;
+
+/// Compares two Debian version strings (`[epoch:]upstream[-revision]`).
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let (epoch_a, rest_a) = split_epoch(a);
+    let (epoch_b, rest_b) = split_epoch(b);
+    match epoch_a.cmp(&epoch_b) {
+        Ordering::Equal => {}
+        other => return other,
+    }
+
+    let (upstream_a, revision_a) = split_revision(rest_a);
+    let (upstream_b, revision_b) = split_revision(rest_b);
+    match compare_part(upstream_a, upstream_b) {
+        Ordering::Equal => compare_part(revision_a, revision_b),
+        other => other,
+    }
+}
+
+/// Whether `candidate` is a newer version than `current` under Debian
+/// ordering; used to decide whether an "upgradable" entry is real.
+pub fn is_newer(current: &str, candidate: &str) -> bool {
+    compare(candidate, current) == Ordering::Greater
+}
+
+fn split_epoch(version: &str) -> (u64, &str) {
+    match version.split_once(':') {
+        Some((epoch, rest)) => (epoch.parse().unwrap_or(0), rest),
+        None => (0, version),
+    }
+}
+
+fn split_revision(version: &str) -> (&str, &str) {
+    match version.rfind('-') {
+        Some(i) => (&version[..i], &version[i + 1..]),
+        None => (version, ""),
+    }
+}
+
+/// Splits a version part into alternating non-digit/digit segments, e.g.
+/// `"1.2a3"` -> `["", "1", ".", "2", "a", "3"]` (the leading "" keeps the
+/// alternation starting on a non-digit segment even when the string
+/// starts with a digit).
+fn segments(part: &str) -> Vec<&str> {
+    let mut segs = Vec::new();
+    let mut chars = part.char_indices().peekable();
+    let mut want_digit = false;
+    let mut start = 0;
+    if let Some(&(_, c)) = chars.peek() {
+        want_digit = c.is_ascii_digit();
+        if want_digit {
+            segs.push("");
+        }
+    }
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_ascii_digit() != want_digit {
+            segs.push(&part[start..i]);
+            start = i;
+            want_digit = !want_digit;
+        }
+        chars.next();
+    }
+    segs.push(&part[start..]);
+    segs
+}
+
+fn compare_part(a: &str, b: &str) -> Ordering {
+    let segs_a = segments(a);
+    let segs_b = segments(b);
+    let len = segs_a.len().max(segs_b.len());
+    for i in 0..len {
+        let sa = segs_a.get(i).copied().unwrap_or("");
+        let sb = segs_b.get(i).copied().unwrap_or("");
+        let ordering = if i % 2 == 0 {
+            compare_non_digit(sa, sb)
+        } else {
+            compare_digit(sa, sb)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// `~` sorts before everything, including the empty string that marks
+/// end-of-part; letters sort before any other non-digit character.
+fn compare_non_digit(a: &str, b: &str) -> Ordering {
+    let mut ia = a.chars();
+    let mut ib = b.chars();
+    loop {
+        let ca = ia.next();
+        let cb = ib.next();
+        match (ca, cb) {
+            (None, None) => return Ordering::Equal,
+            (None, Some('~')) => return Ordering::Greater,
+            (Some('~'), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x == y => continue,
+            (Some(x), Some(y)) => return char_rank(x).cmp(&char_rank(y)),
+        }
+    }
+}
+
+fn char_rank(c: char) -> (u8, char) {
+    if c == '~' {
+        (0, c)
+    } else if c.is_ascii_alphabetic() {
+        (1, c)
+    } else {
+        (2, c)
+    }
+}
+
+fn compare_digit(a: &str, b: &str) -> Ordering {
+    let na: u64 = a.trim_start_matches('0').parse().unwrap_or(0);
+    let nb: u64 = b.trim_start_matches('0').parse().unwrap_or(0);
+    na.cmp(&nb)
+}
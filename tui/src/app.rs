@@ -0,0 +1,338 @@
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command as AsyncCommand;
+
+#[derive(Clone)]
+pub struct Package {
+    pub name: String,
+    pub source: Source,
+    pub description: String,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Source {
+    Apt,
+    Snap,
+    Flatpak,
+    All,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Apt => "APT",
+            Source::Snap => "SNAP",
+            Source::Flatpak => "FLATPAK",
+            Source::All => "ALL",
+        }
+    }
+
+    /// Backend priority used to pick a default source for a row offered by
+    /// more than one backend: apt first, then snap, then flatpak.
+    fn priority(&self) -> u8 {
+        match self {
+            Source::Apt => 0,
+            Source::Snap => 1,
+            Source::Flatpak => 2,
+            Source::All => 3,
+        }
+    }
+}
+
+/// One row of ranked search results: every source offering a package with
+/// the same normalized name collapses into a single row, so the same
+/// program found in e.g. both apt and flatpak shows up once.
+#[derive(Clone)]
+pub struct RankedPackage {
+    pub name: String,
+    pub sources: Vec<Source>,
+    pub description: String,
+}
+
+impl RankedPackage {
+    /// The source `i`/`r` should act on: the current source filter, when
+    /// it names one of this row's backends, otherwise the highest-priority
+    /// backend that offers it.
+    pub fn install_source(&self, filter: &Source) -> Source {
+        if *filter != Source::All && self.sources.contains(filter) {
+            return filter.clone();
+        }
+        self.sources
+            .iter()
+            .min_by_key(|s| s.priority())
+            .cloned()
+            .unwrap_or(Source::Apt)
+    }
+
+    /// Builds the `Package` an install/remove call acts on, honoring the
+    /// active source filter the same way `install_source` does.
+    pub fn to_package(&self, filter: &Source) -> Package {
+        Package {
+            name: self.name.clone(),
+            source: self.install_source(filter),
+            description: self.description.clone(),
+        }
+    }
+}
+
+/// Exact name match scores highest, then a name prefix, then a substring
+/// of the name, then a substring of the description; anything else scores
+/// 0 so unrelated rows sink to the bottom without being dropped outright.
+fn score(query: &str, pkg: &Package) -> u8 {
+    let query = query.to_lowercase();
+    let name = pkg.name.to_lowercase();
+    if name == query {
+        4
+    } else if name.starts_with(&query) {
+        3
+    } else if name.contains(&query) {
+        2
+    } else if pkg.description.to_lowercase().contains(&query) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Scores every result against `query`, stable-sorts descending so ties
+/// keep backend order (apt, snap, flatpak), then collapses rows whose
+/// lowercased name matches into one `RankedPackage` listing every source
+/// that offers it.
+pub fn rank_and_group(query: &str, mut packages: Vec<Package>) -> Vec<RankedPackage> {
+    packages.sort_by(|a, b| score(query, b).cmp(&score(query, a)));
+    let mut grouped: Vec<RankedPackage> = Vec::new();
+    for pkg in packages {
+        let key = pkg.name.to_lowercase();
+        if let Some(existing) = grouped.iter_mut().find(|g| g.name.to_lowercase() == key) {
+            if !existing.sources.contains(&pkg.source) {
+                existing.sources.push(pkg.source);
+            }
+        } else {
+            grouped.push(RankedPackage {
+                name: pkg.name,
+                sources: vec![pkg.source],
+                description: pkg.description,
+            });
+        }
+    }
+    grouped
+}
+
+pub enum InputMode {
+    Normal,
+    Editing,
+}
+
+pub struct App {
+    pub input: String,
+    pub input_mode: InputMode,
+    pub packages: Vec<RankedPackage>,
+    pub package_list_state: ratatui::widgets::ListState,
+    pub selected_source: Source,
+    pub message: String,
+    pub dot_count: usize,
+    /// Set once `prime_privilege` succeeds, so later installs/removes skip
+    /// straight to the command instead of re-prompting for a password.
+    pub sudo_primed: bool,
+}
+
+impl App {
+    pub fn new() -> App {
+        App {
+            input: String::new(),
+            input_mode: InputMode::Normal,
+            packages: Vec::new(),
+            package_list_state: ratatui::widgets::ListState::default(),
+            selected_source: Source::All,
+            message: String::new(),
+            dot_count: 0,
+            sudo_primed: false,
+        }
+    }
+
+    pub fn get_filtered_packages(&self) -> Vec<RankedPackage> {
+        if self.selected_source == Source::All {
+            self.packages.clone()
+        } else {
+            self.packages
+                .iter()
+                .filter(|p| p.sources.contains(&self.selected_source))
+                .cloned()
+                .collect()
+        }
+    }
+}
+
+#[tracing::instrument(skip(input), fields(query = %input))]
+pub async fn search_packages(input: String) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    packages.extend(search_apt(&input).await?);
+    packages.extend(search_snap(&input).await?);
+    packages.extend(search_flatpak(&input).await?);
+    tracing::info!(count = packages.len(), "search completed");
+    Ok(packages)
+}
+
+async fn search_apt(input: &str) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    let output = AsyncCommand::new("apt-cache")
+        .arg("search")
+        .arg("--names-only")
+        .arg(input)
+        .output()
+        .await
+        .context("Failed to execute apt-cache search")?;
+    if output.status.success() {
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let trimmed = line.trim();
+            if let Some((name, desc)) = trimmed.split_once(" - ") {
+                packages.push(Package {
+                    name: name.trim().to_string(),
+                    source: Source::Apt,
+                    description: desc.trim().to_string(),
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+async fn search_snap(input: &str) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    let output = AsyncCommand::new("snap")
+        .arg("find")
+        .arg(input)
+        .output()
+        .await
+        .context("Failed to execute snap find")?;
+    if output.status.success() {
+        let text = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = text.lines().collect();
+        let start = if !lines.is_empty() && lines[0].contains("Name") { 1 } else { 0 };
+        for line in &lines[start..] {
+            let parts: Vec<&str> = line.trim().split_whitespace().collect();
+            if parts.len() >= 5 {
+                packages.push(Package {
+                    name: parts[0].to_string(),
+                    source: Source::Snap,
+                    description: parts[4..].join(" "),
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+async fn search_flatpak(input: &str) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    let output = AsyncCommand::new("flatpak")
+        .arg("search")
+        .arg(input)
+        .output()
+        .await
+        .context("Failed to execute flatpak search")?;
+    if output.status.success() {
+        let text = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = text.lines().collect();
+        let start = if !lines.is_empty() && lines[0].contains("Name") { 1 } else { 0 };
+        for line in &lines[start..] {
+            let parts: Vec<&str> = line.trim().split('\t').collect();
+            if parts.len() >= 3 {
+                packages.push(Package {
+                    name: parts[2].to_string(),
+                    source: Source::Flatpak,
+                    description: format!("{} - {}", parts.first().unwrap_or(&""), parts.get(1).unwrap_or(&"")),
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// Every source's install/remove runs through `sudo`, so every source but
+/// `All` (which is never itself installed) needs a cached credential.
+pub fn requires_privilege(source: &Source) -> bool {
+    !matches!(source, Source::All)
+}
+
+/// Runs `sudo -v` once to cache a credential. The caller is expected to
+/// have already left the alternate screen so the password prompt renders
+/// normally; a non-zero exit (wrong password, or the user aborting with
+/// Ctrl-C) is surfaced as an error rather than silently retried.
+pub async fn prime_privilege() -> Result<()> {
+    let status = AsyncCommand::new("sudo")
+        .arg("-v")
+        .status()
+        .await
+        .context("Failed to run sudo -v")?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("sudo -v did not succeed"))
+    }
+}
+
+/// Refreshes the cached credential with `sudo -n -v` every 60 seconds so
+/// a later install/remove never blocks on a prompt mid-screen.
+pub fn spawn_sudo_refresh() {
+    tokio::spawn(async {
+        let mut ticker = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            ticker.tick().await;
+            let _ = AsyncCommand::new("sudo")
+                .arg("-n")
+                .arg("-v")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await;
+        }
+    });
+}
+
+#[tracing::instrument(skip(pkg), fields(name = %pkg.name, source = pkg.source.as_str()))]
+pub async fn install_package(pkg: Package) -> Result<String> {
+    let (cmd, args) = match pkg.source {
+        Source::Apt => ("apt", vec!["install", "-y", pkg.name.as_str()]),
+        Source::Snap => ("snap", vec!["install", pkg.name.as_str()]),
+        Source::Flatpak => ("flatpak", vec!["install", "--assumeyes", pkg.name.as_str()]),
+        Source::All => return Ok(crate::fl!("invalid-source")),
+    };
+    let output = AsyncCommand::new("sudo")
+        .arg(cmd)
+        .args(&args)
+        .output()
+        .await
+        .context("Failed to install package")?;
+    if output.status.success() {
+        Ok(crate::fl!("installed-from", "name" => pkg.name, "source" => pkg.source.as_str()))
+    } else {
+        let error = format!("{} from {}: {}", pkg.name, pkg.source.as_str(), String::from_utf8_lossy(&output.stderr));
+        tracing::error!(%error, "install failed");
+        Err(anyhow::anyhow!(crate::fl!("install-failed", "error" => error)))
+    }
+}
+
+#[tracing::instrument(skip(pkg), fields(name = %pkg.name, source = pkg.source.as_str()))]
+pub async fn remove_package(pkg: Package) -> Result<String> {
+    let (cmd, args) = match pkg.source {
+        Source::Apt => ("apt", vec!["remove", "-y", pkg.name.as_str()]),
+        Source::Snap => ("snap", vec!["remove", pkg.name.as_str()]),
+        Source::Flatpak => ("flatpak", vec!["uninstall", "--assumeyes", pkg.name.as_str()]),
+        Source::All => return Ok(crate::fl!("invalid-source")),
+    };
+    let output = AsyncCommand::new("sudo")
+        .arg(cmd)
+        .args(&args)
+        .output()
+        .await
+        .context("Failed to remove package")?;
+    if output.status.success() {
+        Ok(crate::fl!("removed-from", "name" => pkg.name, "source" => pkg.source.as_str()))
+    } else {
+        let error = format!("{} from {}: {}", pkg.name, pkg.source.as_str(), String::from_utf8_lossy(&output.stderr));
+        tracing::error!(%error, "remove failed");
+        Err(anyhow::anyhow!(crate::fl!("remove-failed", "error" => error)))
+    }
+}
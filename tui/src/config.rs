@@ -0,0 +1,261 @@
+//! User-configurable keybindings and toggles. `load()` reads
+//! `$XDG_CONFIG_HOME/hpm/config.ron` (default `~/.config/hpm/config.ron`;
+//! a `Config` in RON), falling back to the
+//! defaults below whenever the file is absent or fails to parse, so a bad
+//! or missing config never blocks startup.
+use crate::app::Source;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Quit,
+    Edit,
+    MoveDown,
+    MoveUp,
+    Search,
+    Install,
+    Remove,
+    SelectSource(SourceKey),
+}
+
+/// `serde`-friendly stand-in for `app::Source`: `Source` derives neither
+/// `serde`'s traits nor `Copy`/`Hash`, so bindings pick one of these
+/// instead and `SourceKey::into` converts it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SourceKey {
+    Apt,
+    Snap,
+    Flatpak,
+    All,
+}
+
+impl From<SourceKey> for Source {
+    fn from(key: SourceKey) -> Source {
+        match key {
+            SourceKey::Apt => Source::Apt,
+            SourceKey::Snap => Source::Snap,
+            SourceKey::Flatpak => Source::Flatpak,
+            SourceKey::All => Source::All,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub code: KeyCodeDef,
+    pub modifiers: ModifiersDef,
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> KeyBinding {
+        KeyBinding {
+            code: KeyCodeDef(event.code),
+            modifiers: ModifiersDef(event.modifiers),
+        }
+    }
+}
+
+/// Deriving `serde` on a newtype only delegates to the inner type's own
+/// impl rather than synthesizing one — which `crossterm` doesn't provide
+/// for `KeyCode`/`KeyModifiers` without its `serde` feature, a dependency this crate doesn't take on just for a
+/// config file. So these wrappers round-trip through `KeyCodeRepr`/`u8`
+/// instead, via `#[serde(from = ..., into = ...)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "KeyCodeRepr", into = "KeyCodeRepr")]
+pub struct KeyCodeDef(pub KeyCode);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u8", into = "u8")]
+pub struct ModifiersDef(pub KeyModifiers);
+
+impl From<ModifiersDef> for u8 {
+    fn from(modifiers: ModifiersDef) -> u8 {
+        modifiers.0.bits()
+    }
+}
+
+impl From<u8> for ModifiersDef {
+    fn from(bits: u8) -> ModifiersDef {
+        ModifiersDef(KeyModifiers::from_bits_truncate(bits))
+    }
+}
+
+/// Every `KeyCode` variant a keybinding can plausibly name, serialized
+/// directly instead of through `crossterm`. `Media`/`Modifier` key codes
+/// (media keys, a bare modifier press reported as its own event) aren't
+/// meaningful as bindable actions, so both collapse to `Null`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyCodeRepr {
+    Backspace,
+    Enter,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Tab,
+    BackTab,
+    Delete,
+    Insert,
+    F(u8),
+    Char(char),
+    Null,
+    Esc,
+    CapsLock,
+    ScrollLock,
+    NumLock,
+    PrintScreen,
+    Pause,
+    Menu,
+    KeypadBegin,
+}
+
+impl From<KeyCodeDef> for KeyCodeRepr {
+    fn from(def: KeyCodeDef) -> KeyCodeRepr {
+        match def.0 {
+            KeyCode::Backspace => KeyCodeRepr::Backspace,
+            KeyCode::Enter => KeyCodeRepr::Enter,
+            KeyCode::Left => KeyCodeRepr::Left,
+            KeyCode::Right => KeyCodeRepr::Right,
+            KeyCode::Up => KeyCodeRepr::Up,
+            KeyCode::Down => KeyCodeRepr::Down,
+            KeyCode::Home => KeyCodeRepr::Home,
+            KeyCode::End => KeyCodeRepr::End,
+            KeyCode::PageUp => KeyCodeRepr::PageUp,
+            KeyCode::PageDown => KeyCodeRepr::PageDown,
+            KeyCode::Tab => KeyCodeRepr::Tab,
+            KeyCode::BackTab => KeyCodeRepr::BackTab,
+            KeyCode::Delete => KeyCodeRepr::Delete,
+            KeyCode::Insert => KeyCodeRepr::Insert,
+            KeyCode::F(n) => KeyCodeRepr::F(n),
+            KeyCode::Char(c) => KeyCodeRepr::Char(c),
+            KeyCode::Null => KeyCodeRepr::Null,
+            KeyCode::Esc => KeyCodeRepr::Esc,
+            KeyCode::CapsLock => KeyCodeRepr::CapsLock,
+            KeyCode::ScrollLock => KeyCodeRepr::ScrollLock,
+            KeyCode::NumLock => KeyCodeRepr::NumLock,
+            KeyCode::PrintScreen => KeyCodeRepr::PrintScreen,
+            KeyCode::Pause => KeyCodeRepr::Pause,
+            KeyCode::Menu => KeyCodeRepr::Menu,
+            KeyCode::KeypadBegin => KeyCodeRepr::KeypadBegin,
+            KeyCode::Media(_) | KeyCode::Modifier(_) => KeyCodeRepr::Null,
+        }
+    }
+}
+
+impl From<KeyCodeRepr> for KeyCodeDef {
+    fn from(repr: KeyCodeRepr) -> KeyCodeDef {
+        KeyCodeDef(match repr {
+            KeyCodeRepr::Backspace => KeyCode::Backspace,
+            KeyCodeRepr::Enter => KeyCode::Enter,
+            KeyCodeRepr::Left => KeyCode::Left,
+            KeyCodeRepr::Right => KeyCode::Right,
+            KeyCodeRepr::Up => KeyCode::Up,
+            KeyCodeRepr::Down => KeyCode::Down,
+            KeyCodeRepr::Home => KeyCode::Home,
+            KeyCodeRepr::End => KeyCode::End,
+            KeyCodeRepr::PageUp => KeyCode::PageUp,
+            KeyCodeRepr::PageDown => KeyCode::PageDown,
+            KeyCodeRepr::Tab => KeyCode::Tab,
+            KeyCodeRepr::BackTab => KeyCode::BackTab,
+            KeyCodeRepr::Delete => KeyCode::Delete,
+            KeyCodeRepr::Insert => KeyCode::Insert,
+            KeyCodeRepr::F(n) => KeyCode::F(n),
+            KeyCodeRepr::Char(c) => KeyCode::Char(c),
+            KeyCodeRepr::Null => KeyCode::Null,
+            KeyCodeRepr::Esc => KeyCode::Esc,
+            KeyCodeRepr::CapsLock => KeyCode::CapsLock,
+            KeyCodeRepr::ScrollLock => KeyCode::ScrollLock,
+            KeyCodeRepr::NumLock => KeyCode::NumLock,
+            KeyCodeRepr::PrintScreen => KeyCode::PrintScreen,
+            KeyCodeRepr::Pause => KeyCode::Pause,
+            KeyCodeRepr::Menu => KeyCode::Menu,
+            KeyCodeRepr::KeypadBegin => KeyCode::KeypadBegin,
+        })
+    }
+}
+
+fn binding(code: KeyCode) -> KeyBinding {
+    KeyBinding {
+        code: KeyCodeDef(code),
+        modifiers: ModifiersDef(KeyModifiers::NONE),
+    }
+}
+
+/// The keymap every user started with before `config.ron` existed.
+pub fn default_bindings() -> HashMap<KeyBinding, Action> {
+    let mut bindings = HashMap::new();
+    bindings.insert(binding(KeyCode::Char('q')), Action::Quit);
+    bindings.insert(binding(KeyCode::Char('e')), Action::Edit);
+    bindings.insert(binding(KeyCode::Down), Action::MoveDown);
+    bindings.insert(binding(KeyCode::Char('j')), Action::MoveDown);
+    bindings.insert(binding(KeyCode::Up), Action::MoveUp);
+    bindings.insert(binding(KeyCode::Char('k')), Action::MoveUp);
+    bindings.insert(binding(KeyCode::Enter), Action::Search);
+    bindings.insert(binding(KeyCode::Char('i')), Action::Install);
+    bindings.insert(binding(KeyCode::Char('r')), Action::Remove);
+    bindings.insert(binding(KeyCode::Char('a')), Action::SelectSource(SourceKey::Apt));
+    bindings.insert(binding(KeyCode::Char('s')), Action::SelectSource(SourceKey::Snap));
+    bindings.insert(binding(KeyCode::Char('f')), Action::SelectSource(SourceKey::Flatpak));
+    bindings.insert(binding(KeyCode::Char('l')), Action::SelectSource(SourceKey::All));
+    bindings
+}
+
+fn default_notifications() -> bool {
+    true
+}
+
+/// The full contents of `config.ron`. Both fields are `#[serde(default)]`
+/// so a config that only overrides one of them (or predates the other's
+/// introduction) still loads instead of falling all the way back.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_bindings")]
+    pub bindings: HashMap<KeyBinding, Action>,
+    /// Fire a desktop notification when an install/remove finishes.
+    /// Defaults on; headless/CI runs can set this to `false`.
+    #[serde(default = "default_notifications")]
+    pub notifications: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            bindings: default_bindings(),
+            notifications: default_notifications(),
+        }
+    }
+}
+
+fn config_path() -> Option<std::path::PathBuf> {
+    Some(dirs_config_home()?.join("hpm").join("config.ron"))
+}
+
+/// `dirs`-style `$XDG_CONFIG_HOME` (falling back to `~/.config`) lookup,
+/// kept local since this is the only place the crate needs it.
+fn dirs_config_home() -> Option<std::path::PathBuf> {
+    if let Ok(xdg) = std::env::var("XDG_CONFIG_HOME") {
+        if !xdg.is_empty() {
+            return Some(std::path::PathBuf::from(xdg));
+        }
+    }
+    let home = std::env::var("HOME").ok()?;
+    Some(std::path::PathBuf::from(home).join(".config"))
+}
+
+/// Loads `config.ron`, falling back to `Config::default()` when the file
+/// is missing or doesn't parse.
+pub fn load() -> Config {
+    let Some(path) = config_path() else {
+        return Config::default();
+    };
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return Config::default();
+    };
+    ron::from_str(&contents).unwrap_or_else(|_| Config::default())
+}
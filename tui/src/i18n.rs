@@ -0,0 +1,69 @@
+//! Fluent-backed translations for every user-facing string in the TUI.
+//! Only `en-US` ships today, so there's nothing to resolve from
+//! `$LC_MESSAGES`/`$LANG` yet; the bundle is cached in a global and `fl!`
+//! is the only thing the rest of the crate needs to know about.
+//! Translators add a locale by dropping a new `.ftl` file and restoring
+//! `$LC_MESSAGES`/`$LANG` detection to pick between them, and a missing
+//! id falls back to the bare id rather than panicking.
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use std::sync::{Mutex, OnceLock};
+use unic_langid::LanguageIdentifier;
+
+static EN_US: &str = include_str!("../locales/en-US/main.ftl");
+
+static BUNDLE: OnceLock<Mutex<FluentBundle<FluentResource>>> = OnceLock::new();
+
+fn build_bundle(locale: &str, ftl: &str) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = locale.parse().unwrap_or_else(|_| "en-US".parse().unwrap());
+    let mut bundle = FluentBundle::new(vec![langid]);
+    // The terminal has no bidi renderer, so the default isolate marks
+    // around interpolated values would show up as stray blanks/boxes.
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(ftl.to_string()).unwrap_or_else(|(res, _errors)| res);
+    bundle
+        .add_resource(resource)
+        .expect("built-in .ftl resources must not define duplicate messages");
+    bundle
+}
+
+/// Must be called once before any `fl!` lookup; later calls are no-ops.
+pub fn init() {
+    let _ = BUNDLE.set(Mutex::new(build_bundle("en-US", EN_US)));
+}
+
+/// Looks up `id` in the active bundle and formats it with `args`, using
+/// the `en-US` bundle if `init` was never called. Falls back to the bare
+/// message id if the id or its value is missing, so a typo'd id shows up
+/// instead of crashing the TUI.
+pub fn translate(id: &str, args: &[(&str, String)]) -> String {
+    let bundle = BUNDLE.get_or_init(|| Mutex::new(build_bundle("en-US", EN_US)));
+    let bundle = bundle.lock().unwrap();
+
+    let Some(message) = bundle.get_message(id) else {
+        return id.to_string();
+    };
+    let Some(pattern) = message.value() else {
+        return id.to_string();
+    };
+
+    let mut fargs = FluentArgs::new();
+    for (key, value) in args {
+        fargs.set(*key, FluentValue::from(value.clone()));
+    }
+
+    let mut errors = Vec::new();
+    let formatted = bundle.format_pattern(pattern, Some(&fargs), &mut errors);
+    formatted.into_owned()
+}
+
+/// Looks up a Fluent message by id, optionally interpolating named
+/// arguments: `fl!("installed-from", "name" => pkg.name, "source" => src)`.
+#[macro_export]
+macro_rules! fl {
+    ($id:expr) => {
+        $crate::i18n::translate($id, &[])
+    };
+    ($id:expr, $($key:expr => $val:expr),+ $(,)?) => {
+        $crate::i18n::translate($id, &[$(($key, $val.to_string())),+])
+    };
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use app::{App, InputMode, Package, Source, install_package, remove_package, search_packages};
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{generate, Shell};
+use config::Action;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
@@ -14,6 +16,8 @@ use tokio::time;
 use ui::ui;
 
 mod app;
+mod config;
+mod i18n;
 mod ui;
 
 #[derive(Parser, Debug)]
@@ -23,22 +27,115 @@ struct Args {
     /// Initial search query
     #[arg(short, long)]
     query: Option<String>,
+    /// Increase log verbosity (-v: warn, -vv: info, -vvv: debug, -vvvv: trace)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+/// Maps a repeated `-v` count to the level it turns logging up to, so
+/// `-vvv` reads the same way it does in most CLIs (more `v`s, more noise).
+fn verbosity_level(count: u8) -> tracing::Level {
+    match count {
+        0 => tracing::Level::ERROR,
+        1 => tracing::Level::WARN,
+        2 => tracing::Level::INFO,
+        3 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    }
+}
+
+/// Logs to a daily-rotating file under `~/.local/state/hpm/logs` (or
+/// `/tmp` if `$HOME` isn't set) for the run's lifetime; the returned guard
+/// must be kept alive until the TUI tears down, since the writer behind it
+/// flushes on drop.
+fn init_logging(verbose: u8) -> tracing_appender::non_blocking::WorkerGuard {
+    let log_dir = std::env::var("HOME")
+        .map(|home| std::path::PathBuf::from(home).join(".local/state/hpm/logs"))
+        .unwrap_or_else(|_| std::path::PathBuf::from("/tmp/hpm/logs"));
+    let _ = std::fs::create_dir_all(&log_dir);
+    let file_appender = tracing_appender::rolling::daily(log_dir, "hpm.log");
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+    tracing_subscriber::fmt()
+        .with_writer(non_blocking)
+        .with_max_level(verbosity_level(verbose))
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+        .init();
+    guard
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Install a package without launching the TUI
+    Install {
+        /// Package name to install
+        package: String,
+        #[arg(short, long, default_value = "apt")]
+        source: SourceArg,
+    },
+    /// Remove a package without launching the TUI
+    Remove {
+        /// Package name to remove
+        package: String,
+        #[arg(short, long, default_value = "apt")]
+        source: SourceArg,
+    },
+    /// Search for packages and print the results without launching the TUI
+    Search {
+        /// Search query
+        query: String,
+        #[arg(short, long, default_value = "all")]
+        source: SourceArg,
+    },
+    /// Generate a shell completion script for this CLI
+    Completions {
+        shell: Shell,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum SourceArg {
+    Apt,
+    Snap,
+    Flatpak,
+    All,
+}
+
+impl From<SourceArg> for Source {
+    fn from(source: SourceArg) -> Source {
+        match source {
+            SourceArg::Apt => Source::Apt,
+            SourceArg::Snap => Source::Snap,
+            SourceArg::Flatpak => Source::Flatpak,
+            SourceArg::All => Source::All,
+        }
+    }
 }
 
 pub enum AppMessage {
     SearchComplete(Result<Vec<Package>>),
     InstallComplete(Result<String>),
     RemoveComplete(Result<String>),
+    /// Carries the outcome of `app::prime_privilege`, fired once after the
+    /// alternate screen is suspended to collect a sudo credential.
+    PrivilegeReady(Result<()>),
     Tick,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    i18n::init();
     let args = Args::parse();
+    let _log_guard = init_logging(args.verbose);
+    if let Some(command) = args.command {
+        return run_command(command).await;
+    }
     let mut app = App::new();
     if let Some(query) = args.query {
         app.input = query;
-        app.packages = search_packages(app.input.clone()).await?;
+        let pkgs = search_packages(app.input.clone()).await?;
+        app.packages = app::rank_and_group(&app.input, pkgs);
     }
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -54,7 +151,48 @@ async fn main() -> Result<()> {
     )?;
     terminal.show_cursor()?;
     if let Err(err) = res {
-        println!("{err:?}");
+        eprintln!("{err:?}");
+    }
+    Ok(())
+}
+
+/// Runs one non-interactive subcommand and prints its result to stdout,
+/// so the crate can be scripted from shell/CI without the alternate-screen
+/// TUI `run_app` drives.
+async fn run_command(command: Commands) -> Result<()> {
+    match command {
+        Commands::Install { package, source } => {
+            let pkg = Package {
+                name: package,
+                source: Source::from(source),
+                description: String::new(),
+            };
+            match install_package(pkg).await {
+                Ok(msg) => println!("{msg}"),
+                Err(err) => println!("{err}"),
+            }
+        }
+        Commands::Remove { package, source } => {
+            let pkg = Package {
+                name: package,
+                source: Source::from(source),
+                description: String::new(),
+            };
+            match remove_package(pkg).await {
+                Ok(msg) => println!("{msg}"),
+                Err(err) => println!("{err}"),
+            }
+        }
+        Commands::Search { query, source } => {
+            let source = Source::from(source);
+            let packages = search_packages(query).await?;
+            for pkg in packages.iter().filter(|p| source == Source::All || p.source == source) {
+                println!("{} [{}] - {}", pkg.name, pkg.source.as_str(), pkg.description);
+            }
+        }
+        Commands::Completions { shell } => {
+            generate(shell, &mut Args::command(), "hpm", &mut io::stdout());
+        }
     }
     Ok(())
 }
@@ -65,6 +203,11 @@ async fn run_app(
 ) -> Result<()> {
     let mut event_stream = event::EventStream::new();
     let (update_tx, mut update_rx) = mpsc::channel::<AppMessage>(10);
+    let config = config::load();
+    // The install/remove left for after `prime_privilege` finishes, since
+    // suspending the alternate screen for a password prompt can't also
+    // carry out the operation that triggered it.
+    let mut pending_action: Option<(bool, Package)> = None;
 
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
@@ -73,10 +216,14 @@ async fn run_app(
             Some(event) = event_stream.next() => {
                 if let Ok(Event::Key(key)) = event {
                     match app.input_mode {
-                        InputMode::Normal => match key.code {
-                            KeyCode::Char('q') => return Ok(()),
-                            KeyCode::Char('e') => app.input_mode = InputMode::Editing,
-                            KeyCode::Down | KeyCode::Char('j') => {
+                        InputMode::Normal => {
+                            let Some(action) = config.bindings.get(&config::KeyBinding::from(key)).copied() else {
+                                continue;
+                            };
+                            match action {
+                            Action::Quit => return Ok(()),
+                            Action::Edit => app.input_mode = InputMode::Editing,
+                            Action::MoveDown => {
                                 if let Some(selected) = app.package_list_state.selected() {
                                     let len = app.get_filtered_packages().len();
                                     if selected + 1 < len {
@@ -86,19 +233,19 @@ async fn run_app(
                                     app.package_list_state.select(Some(0));
                                 }
                             }
-                            KeyCode::Up | KeyCode::Char('k') => {
+                            Action::MoveUp => {
                                 if let Some(selected) = app.package_list_state.selected() {
                                     if selected > 0 {
                                         app.package_list_state.select(Some(selected - 1));
                                     }
                                 }
                             }
-                            KeyCode::Enter => {
+                            Action::Search => {
                                 if app.input.is_empty() {
-                                    app.message = "Enter a search query.".to_string();
+                                    app.message = fl!("enter-search-query");
                                     continue;
                                 }
-                                app.message = "Searching".to_string();
+                                app.message = fl!("searching");
                                 app.dot_count = 0;
                                 let input = app.input.clone();
                                 let tx = update_tx.clone();
@@ -121,51 +268,49 @@ async fn run_app(
                                     }
                                 });
                             }
-                            KeyCode::Char('i') => {
+                            Action::Install => {
                                 if let Some(selected) = app.package_list_state.selected() {
                                     let filtered = app.get_filtered_packages();
-                                    if let Some(pkg) = filtered.get(selected) {
-                                        let pkg = pkg.clone();
-                                        let tx = update_tx.clone();
-                                        app.message = "Installing...".to_string();
-                                        tokio::spawn(async move {
-                                            let res = install_package(pkg).await;
-                                            let _ = tx.send(AppMessage::InstallComplete(res)).await;
-                                        });
+                                    if let Some(ranked) = filtered.get(selected) {
+                                        let pkg = ranked.to_package(&app.selected_source);
+                                        if app::requires_privilege(&pkg.source) && !app.sudo_primed {
+                                            pending_action = Some((true, pkg));
+                                            request_privilege(terminal, &mut app, &update_tx).await?;
+                                        } else {
+                                            let tx = update_tx.clone();
+                                            app.message = fl!("installing");
+                                            tokio::spawn(async move {
+                                                let res = install_package(pkg).await;
+                                                let _ = tx.send(AppMessage::InstallComplete(res)).await;
+                                            });
+                                        }
                                     }
                                 }
                             }
-                            KeyCode::Char('r') => {
+                            Action::Remove => {
                                 if let Some(selected) = app.package_list_state.selected() {
                                     let filtered = app.get_filtered_packages();
-                                    if let Some(pkg) = filtered.get(selected) {
-                                        let pkg = pkg.clone();
-                                        let tx = update_tx.clone();
-                                        app.message = "Removing...".to_string();
-                                        tokio::spawn(async move {
-                                            let res = remove_package(pkg).await;
-                                            let _ = tx.send(AppMessage::RemoveComplete(res)).await;
-                                        });
+                                    if let Some(ranked) = filtered.get(selected) {
+                                        let pkg = ranked.to_package(&app.selected_source);
+                                        if app::requires_privilege(&pkg.source) && !app.sudo_primed {
+                                            pending_action = Some((false, pkg));
+                                            request_privilege(terminal, &mut app, &update_tx).await?;
+                                        } else {
+                                            let tx = update_tx.clone();
+                                            app.message = fl!("removing");
+                                            tokio::spawn(async move {
+                                                let res = remove_package(pkg).await;
+                                                let _ = tx.send(AppMessage::RemoveComplete(res)).await;
+                                            });
+                                        }
                                     }
                                 }
                             }
-                            KeyCode::Char('a') => {
-                                app.selected_source = Source::Apt;
+                            Action::SelectSource(source) => {
+                                app.selected_source = Source::from(source);
                                 update_selection(&mut app);
                             }
-                            KeyCode::Char('s') => {
-                                app.selected_source = Source::Snap;
-                                update_selection(&mut app);
-                            }
-                            KeyCode::Char('f') => {
-                                app.selected_source = Source::Flatpak;
-                                update_selection(&mut app);
-                            }
-                            KeyCode::Char('l') => {
-                                app.selected_source = Source::All;
-                                update_selection(&mut app);
                             }
-                            _ => {}
                         },
                         InputMode::Editing => match key.code {
                             KeyCode::Enter => {
@@ -186,34 +331,72 @@ async fn run_app(
                     AppMessage::SearchComplete(res) => {
                         match res {
                             Ok(pkgs) => {
-                                app.packages = pkgs;
+                                app.packages = app::rank_and_group(&app.input, pkgs);
                                 if app.packages.is_empty() {
-                                    app.message = "No packages found.".to_string();
+                                    app.message = fl!("no-packages-found");
                                 } else {
                                     app.message = String::new();
                                     update_selection(&mut app);
                                 }
                             }
                             Err(e) => {
-                                app.message = format!("Search failed: {}", e);
+                                app.message = fl!("search-failed", "error" => e);
                             }
                         }
                     }
                     AppMessage::InstallComplete(res) => {
                         match res {
-                            Ok(msg) => app.message = msg,
-                            Err(e) => app.message = format!("Install failed: {}", e),
+                            Ok(msg) => { notify(&config, &msg); app.message = msg; }
+                            Err(e) => {
+                                let msg = fl!("install-failed", "error" => e);
+                                notify(&config, &msg);
+                                app.message = msg;
+                            }
                         }
                     }
                     AppMessage::RemoveComplete(res) => {
                         match res {
-                            Ok(msg) => app.message = msg,
-                            Err(e) => app.message = format!("Remove failed: {}", e),
+                            Ok(msg) => { notify(&config, &msg); app.message = msg; }
+                            Err(e) => {
+                                let msg = fl!("remove-failed", "error" => e);
+                                notify(&config, &msg);
+                                app.message = msg;
+                            }
+                        }
+                    }
+                    AppMessage::PrivilegeReady(res) => {
+                        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+                        enable_raw_mode()?;
+                        match res {
+                            Ok(()) => {
+                                app.sudo_primed = true;
+                                app::spawn_sudo_refresh();
+                                if let Some((install, pkg)) = pending_action.take() {
+                                    let tx = update_tx.clone();
+                                    if install {
+                                        app.message = fl!("installing");
+                                        tokio::spawn(async move {
+                                            let res = install_package(pkg).await;
+                                            let _ = tx.send(AppMessage::InstallComplete(res)).await;
+                                        });
+                                    } else {
+                                        app.message = fl!("removing");
+                                        tokio::spawn(async move {
+                                            let res = remove_package(pkg).await;
+                                            let _ = tx.send(AppMessage::RemoveComplete(res)).await;
+                                        });
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                pending_action = None;
+                                app.message = fl!("authentication-failed", "error" => e);
+                            }
                         }
                     }
                     AppMessage::Tick => {
                         app.dot_count += 1;
-                        app.message = "Searching".to_string() + &".".repeat(app.dot_count % 4);
+                        app.message = fl!("searching") + &".".repeat(app.dot_count % 4);
                     }
                 }
             }
@@ -221,6 +404,37 @@ async fn run_app(
     }
 }
 
+/// Suspends the alternate screen so a `sudo -v` password prompt renders
+/// normally, then kicks off `prime_privilege` in the background; the
+/// screen is restored once `AppMessage::PrivilegeReady` arrives.
+async fn request_privilege(
+    terminal: &mut Terminal<impl Backend>,
+    app: &mut App,
+    update_tx: &mpsc::Sender<AppMessage>,
+) -> Result<()> {
+    app.message = fl!("awaiting-authentication");
+    terminal.draw(|f| ui(f, app))?;
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    let tx = update_tx.clone();
+    tokio::spawn(async move {
+        let res = app::prime_privilege().await;
+        let _ = tx.send(AppMessage::PrivilegeReady(res)).await;
+    });
+    Ok(())
+}
+
+/// Fires a desktop notification for a finished install/remove so a user
+/// who tabbed away still learns the outcome. A no-op when
+/// `config.notifications` is off, and failures to show one (no notification
+/// daemon running, headless session) are swallowed rather than surfaced.
+fn notify(config: &config::Config, body: &str) {
+    if !config.notifications {
+        return;
+    }
+    let _ = notify_rust::Notification::new().summary("hpm").body(body).show();
+}
+
 fn update_selection(app: &mut App) {
     let filtered_len = app.get_filtered_packages().len();
     if filtered_len > 0 {
@@ -70,10 +70,11 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     let items: Vec<ListItem> = filtered_packages
     .iter()
     .map(|p| {
+        let sources = p.sources.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
         ListItem::new(Line::from(vec![
             Span::styled(&p.name, Style::default().fg(Color::Green)),
                                  Span::raw(" ("),
-                                 Span::styled(p.source.as_str(), Style::default().fg(Color::Blue)),
+                                 Span::styled(sources, Style::default().fg(Color::Blue)),
                                  Span::raw(") - "),
                                  Span::raw(&p.description),
         ]))